@@ -1,55 +1,150 @@
-use std::{io::Write, mem, thread};
+use std::{fs, io::Write, thread};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
 
 use crate::{
-    euchre::{enums::Team, game::play_euchre},
+    cli::options::{EvolutionOptions, MutationOptions, ReinforcementOptions, TrainingOptions},
+    euchre::{
+        bots::Bot,
+        enums::{ActionIndex, Team},
+        game::{play_euchre, play_euchre_with_summaries, RoundSummary},
+        player::Player,
+        puzzles::PuzzleSuite,
+        statistics::GameStatistics,
+    },
     organism::{
-        evolution::{evolve, Organism},
-        neural_network::NeuralNetwork,
+        benchmark::benchmark,
+        config::{EvolutionConfig, InitConfig, MutationConfig},
+        diversity::{population_diversity, probe_positions, PROBE_SEED},
+        evaluation::partner_robustness,
+        evolution::evolve,
+        neural_network::{AvailableActions, NeuralNetwork, NeuralNetworkInput, Seat},
+        policy::ActionPolicy,
+        quantized::{compare_quantized, Precision, QuantizedNetwork, QuantizedSeat},
+        reinforcement::{train_reinforcement, ReinforcementConfig},
+        significance::{bradley_terry_ratings, IntervalMethod, Sprt},
+        symmetry::invariance,
+        training::{
+            append_records, load_records, train, DecisionRecord, RecordingPlayer, TrainingConfig,
+        },
     },
 };
 
-const VALID_POPULATION_SIZES: [usize; 10] = [2048, 1024, 512, 256, 128, 64, 32, 16, 8, 4];
+// Prefix naming a built-in bot wherever an agent file is expected
+const BOT_PREFIX: &str = "bot:";
+
+// A full network, an inference only quantised one, or a built-in bot
+enum Agent {
+    Network(NeuralNetwork),
+    Quantized(QuantizedNetwork),
+    Bot(Bot),
+}
+
+impl Agent {
+    fn load(filename: &str) -> std::io::Result<Agent> {
+        if let Some(name) = filename.strip_prefix(BOT_PREFIX) {
+            return name
+                .parse()
+                .map(Agent::Bot)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e));
+        }
+        if QuantizedNetwork::is_quantized_file(filename)? {
+            return Ok(Agent::Quantized(QuantizedNetwork::load_from_file(
+                filename,
+            )?));
+        }
+        let mut nn = NeuralNetwork::new();
+        nn.load_from_file(filename)?;
+        Ok(Agent::Network(nn))
+    }
+
+    fn seat(&self, policy: ActionPolicy) -> AgentSeat<'_> {
+        match self {
+            Agent::Network(nn) => AgentSeat::Network(Box::new(nn.seat_with_policy(policy))),
+            Agent::Quantized(quantized) => AgentSeat::Quantized(quantized.seat_with_policy(policy)),
+            Agent::Bot(bot) => AgentSeat::Bot(*bot),
+        }
+    }
+}
+
+enum AgentSeat<'a> {
+    Network(Box<Seat<'a>>),
+    Quantized(QuantizedSeat<'a>),
+    Bot(Bot),
+}
+
+impl AgentSeat<'_> {
+    fn non_finite_decision(&self) -> Option<DecisionRecord> {
+        match self {
+            AgentSeat::Network(seat) => seat.non_finite_decision(),
+            AgentSeat::Quantized(seat) => seat.non_finite_decision(),
+            AgentSeat::Bot(_) => None,
+        }
+    }
+}
+
+impl Player for AgentSeat<'_> {
+    fn get_action(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex {
+        match self {
+            AgentSeat::Network(seat) => seat.get_action(inputs, available_actions),
+            AgentSeat::Quantized(seat) => seat.get_action(inputs, available_actions),
+            AgentSeat::Bot(bot) => bot.get_action(inputs, available_actions),
+        }
+    }
+
+    fn end_round(&self, own_score_delta: u8, other_score_delta: u8) {
+        match self {
+            AgentSeat::Network(seat) => seat.end_round(own_score_delta, other_score_delta),
+            AgentSeat::Quantized(seat) => seat.end_round(own_score_delta, other_score_delta),
+            AgentSeat::Bot(bot) => bot.end_round(own_score_delta, other_score_delta),
+        }
+    }
+}
+
+pub fn load_evolution_config(
+    config_file: Option<String>,
+    options: &EvolutionOptions,
+) -> EvolutionConfig {
+    let mut config = match config_file {
+        Some(config_file) => match EvolutionConfig::load_from_file(config_file.as_str()) {
+            Ok(config) => config,
+            Err(e) => {
+                println!("Failed to load config {}: {}", config_file, e);
+                std::process::exit(1);
+            }
+        },
+        None => EvolutionConfig::default(),
+    };
+    options.apply(&mut config);
+    if let Err(e) = config.validate() {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+    config
+}
 
-// TODO: better error handling here
 pub fn evolve_cli(
-    population_size: usize,
-    generations: usize,
-    thread_count: usize,
+    config: EvolutionConfig,
     out_dir: String,
     starting_population_dir: Option<String>,
     no_gen_save: bool,
 ) {
-    if !VALID_POPULATION_SIZES.contains(&population_size) {
-        println!(
-            "Invalid population size, valid sizes are: {:?}",
-            VALID_POPULATION_SIZES
-        );
-        std::process::exit(1);
-    }
     println!(
         "Population Size: {}, Generations: {}",
-        population_size, generations
+        config.population_size, config.generations
     );
 
-    // max supported population size + 31, don't ask why
-    let stack_size: usize = mem::size_of::<Organism>() * (2048 + 31);
-
     let handle = thread::Builder::new()
-        .stack_size(stack_size)
         .spawn(move || -> std::io::Result<()> {
-            match population_size {
-                2048 => evolve::<2048, 1024>(generations, out_dir.clone(), thread_count, stack_size, starting_population_dir, no_gen_save).unwrap(),
-                1024 => evolve::<1024, 512>(generations, out_dir.clone(), thread_count, stack_size, starting_population_dir, no_gen_save).unwrap(),
-                512 => evolve::<512, 256>(generations, out_dir.clone(), thread_count, stack_size, starting_population_dir, no_gen_save).unwrap(),
-                256 => evolve::<256, 128>(generations, out_dir.clone(), thread_count, stack_size, starting_population_dir, no_gen_save).unwrap(),
-                128 => evolve::<128, 64>(generations, out_dir.clone(), thread_count, stack_size, starting_population_dir, no_gen_save).unwrap(),
-                64 => evolve::<64, 32>(generations, out_dir.clone(), thread_count, stack_size, starting_population_dir, no_gen_save).unwrap(),
-                32 => evolve::<32, 16>(generations, out_dir.clone(), thread_count, stack_size, starting_population_dir, no_gen_save).unwrap(),
-                16 => evolve::<16, 8>(generations, out_dir.clone(), thread_count, stack_size, starting_population_dir, no_gen_save).unwrap(),
-                8 => evolve::<8, 4>(generations, out_dir.clone(), thread_count, stack_size, starting_population_dir, no_gen_save).unwrap(),
-                4 => evolve::<4, 2>(generations, out_dir.clone(), thread_count, stack_size, starting_population_dir, no_gen_save).unwrap(),
-                _ => panic!("Invalid population size.  Valid populations sizes: [2048, 1024, 512, 256, 128, 64, 32, 16, 8, 4]")
-            };
+            if let Err(e) = evolve(&config, out_dir, starting_population_dir, no_gen_save) {
+                println!("{}", e);
+                std::process::exit(1);
+            }
             Ok(())
         })
         .unwrap();
@@ -57,45 +152,245 @@ pub fn evolve_cli(
     handle.join().unwrap().ok();
 }
 
+pub fn load_mutation_config(
+    config_file: Option<String>,
+    options: &MutationOptions,
+) -> MutationConfig {
+    let mut config = match config_file {
+        Some(config_file) => match EvolutionConfig::load_from_file(config_file.as_str()) {
+            Ok(config) => config.mutation,
+            Err(e) => {
+                println!("Failed to load config {}: {}", config_file, e);
+                std::process::exit(1);
+            }
+        },
+        None => MutationConfig::default(),
+    };
+    options.apply(&mut config);
+    if let Err(e) = config.validate() {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+    config
+}
+
+pub fn load_training_config(options: &TrainingOptions) -> TrainingConfig {
+    let mut config = TrainingConfig::default();
+    options.apply(&mut config);
+    if let Err(e) = config.validate() {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+    config
+}
+
+pub fn load_reinforcement_config(options: &ReinforcementOptions) -> ReinforcementConfig {
+    let mut config = ReinforcementConfig::default();
+    options.apply(&mut config);
+    if let Err(e) = config.validate() {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+    config
+}
+
+// One game of a compete match, with seats indexed by agent rather than by where they sat
+struct CompeteGame {
+    north_south_won: bool,
+    summaries: Vec<RoundSummary>,
+    non_finite: [Option<DecisionRecord>; 4],
+}
+
+// With offset 1 every agent sits one seat to the left of where it was given
+fn play_compete_game(
+    agents: [&Agent; 4],
+    policy: ActionPolicy,
+    deal_seed: u64,
+    offset: usize,
+) -> CompeteGame {
+    let seats: Vec<AgentSeat> = (0..agents.len())
+        .map(|i| agents[(i + offset) % agents.len()].seat(policy))
+        .collect();
+    let mut summaries = Vec::new();
+    let winner = play_euchre_with_summaries(
+        &seats[0],
+        &seats[1],
+        &seats[2],
+        &seats[3],
+        &mut StdRng::seed_from_u64(deal_seed),
+        &mut |summary| summaries.push(summary.rotated(offset)),
+    );
+    let mut non_finite: [Option<DecisionRecord>; 4] = Default::default();
+    for (i, seat) in seats.iter().enumerate() {
+        non_finite[(i + offset) % agents.len()] = seat.non_finite_decision();
+    }
+    CompeteGame {
+        north_south_won: matches!(
+            (winner, offset),
+            (Team::NorthSouth, 0) | (Team::EastWest, 1)
+        ),
+        summaries,
+        non_finite,
+    }
+}
+
+// How compete plays and reports its games, besides who sits where
+pub struct CompeteOptions {
+    pub games: usize,
+    pub policy: ActionPolicy,
+    // per seat and team statistics are also written here as JSON
+    pub stats_file: Option<String>,
+    pub interval: IntervalMethod,
+    // stops early once one team is significantly stronger
+    pub sprt: Option<Sprt>,
+    // plays every deal twice with the teams swapping seats
+    pub duplicate: bool,
+    pub thread_count: usize,
+}
+
 pub fn compete_cli(
     north_player: String,
     east_player: String,
     south_player: String,
     west_player: String,
-    num_games: usize,
+    options: CompeteOptions,
 ) {
-    // max supported population size + 31, don't ask why
-    let stack_size: usize = mem::size_of::<Organism>() * (4 + 31);
-
+    let CompeteOptions {
+        games: num_games,
+        policy,
+        stats_file,
+        interval,
+        sprt,
+        duplicate,
+        thread_count,
+    } = options;
     let handle = thread::Builder::new()
-        .stack_size(stack_size)
         .spawn(move || -> std::io::Result<()> {
-            let mut north = NeuralNetwork::new();
-            let mut east = NeuralNetwork::new();
-            let mut south = NeuralNetwork::new();
-            let mut west = NeuralNetwork::new();
-            north.load_from_file(north_player.as_str())?;
-            east.load_from_file(east_player.as_str())?;
-            south.load_from_file(south_player.as_str())?;
-            west.load_from_file(west_player.as_str())?;
+            let files = [north_player, east_player, south_player, west_player];
+            let mut agents = Vec::with_capacity(files.len());
+            for file in files.iter() {
+                agents.push(Agent::load(file.as_str())?);
+            }
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(thread_count)
+                .build()
+                .unwrap();
+            let mut rng = rand::thread_rng();
+            // duplicate deals are played in pairs
+            let num_games = if duplicate {
+                num_games.next_multiple_of(2)
+            } else {
+                num_games
+            };
+            // the second game of a duplicate pair replays the deal with every agent one seat to
+            // the left, so each team gets the cards the other team had
+            let mut deals: Vec<(u64, usize)> = Vec::with_capacity(num_games);
+            for game in 0..num_games {
+                if duplicate && game % 2 == 1 {
+                    deals.push((deals[game - 1].0, 1));
+                } else {
+                    deals.push((rng.gen::<u64>(), 0));
+                }
+            }
+            // games are played in parallel a batch at a time and counted in order, so with argmax
+            // seats the result doesn't depend on the thread count and the SPRT can stop between
+            // batches
+            let batch_size = (thread_count * 16).next_multiple_of(2);
+            let mut reported = [false; 4];
+            let mut statistics = GameStatistics::default();
             let mut north_south_score = 0;
             let mut east_west_score = 0;
-            let games_to_win: usize = num_games / 2;
-            for _ in 0..num_games {
-                match play_euchre(&north, &east, &south, &west) {
-                    Team::NorthSouth => north_south_score += 1,
-                    Team::EastWest => east_west_score += 1,
+            let mut decided = None;
+            'batches: for batch in deals.chunks(batch_size) {
+                let games: Vec<CompeteGame> = pool.install(|| {
+                    batch
+                        .par_iter()
+                        .map(|&(deal_seed, offset)| {
+                            play_compete_game(
+                                [&agents[0], &agents[1], &agents[2], &agents[3]],
+                                policy,
+                                deal_seed,
+                                offset,
+                            )
+                        })
+                        .collect()
+                });
+                for game in games {
+                    if game.north_south_won {
+                        north_south_score += 1;
+                    } else {
+                        east_west_score += 1;
+                    }
+                    statistics.games += 1;
+                    for summary in game.summaries.iter() {
+                        statistics.record(summary);
+                    }
+                    for (agent, record) in game.non_finite.iter().enumerate() {
+                        if let Some(record) = record.as_ref().filter(|_| !reported[agent]) {
+                            reported[agent] = true;
+                            println!(
+                                "{} produced NaN or infinite outputs for input {}",
+                                files[agent],
+                                serde_json::to_string(record).unwrap()
+                            );
+                        }
+                    }
+                    let played = north_south_score + east_west_score;
+                    if let Some(sprt) = sprt.filter(|_| !duplicate || played % 2 == 0) {
+                        decided = sprt.decision(north_south_score, east_west_score);
+                        if decided.is_some() {
+                            break 'batches;
+                        }
+                    }
                 }
                 println!(
-                    "North/South Score: {}, East/West Score: {}",
-                    north_south_score, east_west_score
+                    "Games {}/{} - North/South Score: {}, East/West Score: {}",
+                    north_south_score + east_west_score,
+                    num_games,
+                    north_south_score,
+                    east_west_score
                 );
-                if north_south_score > games_to_win {
-                    println!("North/South team wins!");
-                } else if east_west_score > games_to_win {
-                    println!("East/West team wins!");
+            }
+            let games = north_south_score + east_west_score;
+            println!(
+                "North/South Score: {}, East/West Score: {}",
+                north_south_score, east_west_score
+            );
+            if north_south_score > east_west_score {
+                println!("North/South team wins!");
+            } else if east_west_score > north_south_score {
+                println!("East/West team wins!");
+            }
+            let (lower, upper) = interval.interval(north_south_score, games, &mut rng);
+            println!(
+                "North/South Win Rate: {}, 95% Interval ({:?}): [{}, {}]",
+                north_south_score as f64 / games as f64,
+                interval,
+                lower,
+                upper
+            );
+            if let Some(sprt) = sprt {
+                let llr = sprt.log_likelihood_ratio(north_south_score, east_west_score);
+                match decided {
+                    Some(true) => println!(
+                        "SPRT - North/South is stronger after {} games, LLR: {}",
+                        games, llr
+                    ),
+                    Some(false) => println!(
+                        "SPRT - East/West is stronger after {} games, LLR: {}",
+                        games, llr
+                    ),
+                    None => println!("SPRT - Inconclusive after {} games, LLR: {}", games, llr),
                 }
             }
+            statistics.print_table();
+            if let Some(stats_file) = stats_file {
+                fs::write(
+                    &stats_file,
+                    serde_json::to_string_pretty(&statistics.to_json()).unwrap(),
+                )?;
+                println!("Saved statistics to {}", stats_file);
+            }
             Ok(())
         })
         .unwrap();
@@ -103,12 +398,134 @@ pub fn compete_cli(
     handle.join().unwrap().ok();
 }
 
-pub fn stats_cli(file: String) {
-    // max supported population size + 31, don't ask why
-    let stack_size: usize = mem::size_of::<Organism>() * (1 + 31);
+// Every pair of entrants plays games_per_pairing duplicate pairs of games, each entrant partnering
+// itself and sitting north/south for one game of each pair
+pub fn league_cli(
+    files: Vec<String>,
+    dir: Option<String>,
+    bots: Vec<Bot>,
+    games_per_pairing: usize,
+    policy: ActionPolicy,
+    thread_count: usize,
+) {
+    if games_per_pairing == 0 {
+        println!("games must be at least 1");
+        std::process::exit(1);
+    }
+    let handle = thread::Builder::new()
+        .spawn(move || -> std::io::Result<()> {
+            let mut names = files;
+            if let Some(dir) = dir {
+                names.extend(network_files_in_dir(dir.as_str())?);
+            }
+            names.extend(
+                bots.iter()
+                    .map(|bot| format!("{}{}", BOT_PREFIX, bot.name())),
+            );
+            if names.len() < 2 {
+                println!("Need at least 2 entrants for a league");
+                std::process::exit(1);
+            }
+            let mut entrants = Vec::with_capacity(names.len());
+            for name in names.iter() {
+                entrants.push(Agent::load(name.as_str())?);
+            }
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(thread_count)
+                .build()
+                .unwrap();
+            let mut rng = rand::thread_rng();
+            let count = entrants.len();
+            let mut wins = vec![vec![0.0; count]; count];
+            let mut points = vec![0; count];
+            let mut games_played = vec![0; count];
+            let pairings: Vec<(usize, usize)> = (0..count)
+                .flat_map(|i| (i + 1..count).map(move |j| (i, j)))
+                .collect();
+            for (pairing, &(i, j)) in pairings.iter().enumerate() {
+                let deals: Vec<(u64, usize)> = (0..games_per_pairing)
+                    .flat_map(|_| {
+                        let deal_seed = rng.gen::<u64>();
+                        [(deal_seed, 0), (deal_seed, 1)]
+                    })
+                    .collect();
+                let games: Vec<CompeteGame> = pool.install(|| {
+                    deals
+                        .par_iter()
+                        .map(|&(deal_seed, offset)| {
+                            let (first, second) = (&entrants[i], &entrants[j]);
+                            play_compete_game(
+                                [first, second, first, second],
+                                policy,
+                                deal_seed,
+                                offset,
+                            )
+                        })
+                        .collect()
+                });
+                for game in games.iter() {
+                    let (winner, loser) = if game.north_south_won { (i, j) } else { (j, i) };
+                    wins[winner][loser] += 1.0;
+                    for summary in game.summaries.iter() {
+                        points[i] += summary.points[0] as usize;
+                        points[j] += summary.points[1] as usize;
+                    }
+                }
+                games_played[i] += games.len();
+                games_played[j] += games.len();
+                println!(
+                    "Pairing {}/{} - {} vs {}: {} - {}",
+                    pairing + 1,
+                    pairings.len(),
+                    names[i],
+                    names[j],
+                    wins[i][j],
+                    wins[j][i]
+                );
+            }
+
+            println!("Crosstable - win rate of the row against the column");
+            print!("{:>4}", "");
+            for j in 0..count {
+                print!("{:>8}", j + 1);
+            }
+            println!();
+            for i in 0..count {
+                print!("{:>4}", i + 1);
+                for (j, &won) in wins[i].iter().enumerate() {
+                    if i == j {
+                        print!("{:>8}", "-");
+                    } else {
+                        print!("{:>8.3}", won / (won + wins[j][i]));
+                    }
+                }
+                println!("  {}", names[i]);
+            }
+
+            let ratings = bradley_terry_ratings(&wins);
+            let mut standings: Vec<usize> = (0..count).collect();
+            standings.sort_by(|&a, &b| ratings[b].total_cmp(&ratings[a]));
+            println!("Standings");
+            for (rank, &i) in standings.iter().enumerate() {
+                let won: f64 = wins[i].iter().sum();
+                println!(
+                    "{}. {} - Win Rate: {}, Points/Game: {}, Rating: {}",
+                    rank + 1,
+                    names[i],
+                    won / games_played[i] as f64,
+                    points[i] as f64 / games_played[i] as f64,
+                    ratings[i]
+                );
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    handle.join().unwrap().ok();
+}
 
+pub fn stats_cli(file: String) {
     let handle = thread::Builder::new()
-        .stack_size(stack_size)
         .spawn(move || -> std::io::Result<()> {
             let mut nn = NeuralNetwork::new();
             nn.load_from_file(file.as_str())?;
@@ -120,20 +537,25 @@ pub fn stats_cli(file: String) {
     handle.join().unwrap().ok();
 }
 
-pub fn breed_cli(parent1_file: String, parent2_file: String, child_file: String) {
-    // max supported population size + 31, don't ask why
-    let stack_size: usize = mem::size_of::<Organism>() * (1 + 31);
-
+pub fn breed_cli(
+    parent1_file: String,
+    parent2_file: String,
+    child_file: String,
+    mutation: MutationConfig,
+) {
     let handle = thread::Builder::new()
-        .stack_size(stack_size)
         .spawn(move || -> std::io::Result<()> {
             let mut parent1 = NeuralNetwork::new();
             let mut parent2 = NeuralNetwork::new();
             parent1.load_from_file(parent1_file.as_str())?;
             parent2.load_from_file(parent2_file.as_str())?;
-            // TODO: mutation rate and magnitude as arguments
-            let child = parent1.crossover(&parent2, 0.01, 0.1);
-            child.save_to_file(child_file.as_str())?;
+            match parent1.crossover(&parent2, &mutation.parameters(1, 1)) {
+                Ok(child) => child.save_to_file(child_file.as_str())?,
+                Err(e) => {
+                    println!("{}", e);
+                    std::process::exit(1);
+                }
+            }
             Ok(())
         })
         .unwrap();
@@ -141,12 +563,14 @@ pub fn breed_cli(parent1_file: String, parent2_file: String, child_file: String)
     handle.join().unwrap().ok();
 }
 
-pub fn tutor_cli(tutor_file: String, left_file: String, right_file: String, ally_file: String) {
-    // max supported population size + 31, don't ask why
-    let stack_size: usize = mem::size_of::<Organism>() * (4 + 31);
-
+pub fn tutor_cli(
+    tutor_file: String,
+    left_file: String,
+    right_file: String,
+    ally_file: String,
+    records_file: Option<String>,
+) {
     let handle = thread::Builder::new()
-        .stack_size(stack_size)
         .spawn(move || -> std::io::Result<()> {
             let mut tutor = NeuralNetwork::new();
             let mut left: NeuralNetwork = NeuralNetwork::new();
@@ -158,7 +582,15 @@ pub fn tutor_cli(tutor_file: String, left_file: String, right_file: String, ally
             ally.load_from_file(ally_file.as_str())?;
             tutor.tutor_mode = true;
             loop {
-                play_euchre(&tutor, &left, &ally, &right);
+                let seat = tutor.seat();
+                let recorder = RecordingPlayer::new(&seat);
+                play_euchre(&recorder, &left.seat(), &ally.seat(), &right.seat());
+                let records = recorder.take_records();
+                if let Some(records_file) = &records_file {
+                    if let Err(e) = append_records(records_file, &records) {
+                        println!("Failed to record decisions to {}: {}", records_file, e);
+                    }
+                }
                 loop {
                     print!("Do you want to continue? [Y/n]: ");
                     std::io::stdout().flush().expect("Failed to flush stdout");
@@ -169,7 +601,7 @@ pub fn tutor_cli(tutor_file: String, left_file: String, right_file: String, ally
                         .expect("Failed to read line");
 
                     let input = input.trim().to_lowercase();
-                    if input == "y" || input == "Y" || input == "" {
+                    if input == "y" || input == "Y" || input.is_empty() {
                         println!("Continuing...");
                         break;
                     } else if input == "n" || input == "N" {
@@ -185,3 +617,400 @@ pub fn tutor_cli(tutor_file: String, left_file: String, right_file: String, ally
 
     handle.join().unwrap().ok();
 }
+
+fn network_files_in_dir(dir: &str) -> std::io::Result<Vec<String>> {
+    let mut file_names: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| {
+            entry
+                .ok()
+                .and_then(|e| e.path().to_str().map(|s| s.to_owned()))
+        })
+        .filter(|file_name| file_name.ends_with(".bin"))
+        .collect();
+    file_names.sort();
+    Ok(file_names)
+}
+
+pub fn robustness_cli(agent_file: String, partners_dir: String, games: usize) {
+    if games == 0 {
+        println!("games must be at least 1");
+        std::process::exit(1);
+    }
+    let handle = thread::Builder::new()
+        .spawn(move || -> std::io::Result<()> {
+            let mut agent = NeuralNetwork::new();
+            agent.load_from_file(agent_file.as_str())?;
+            // the agent doesn't partner or play against itself when it's in the directory
+            let agent_path = fs::canonicalize(&agent_file)?;
+            let mut partner_files = network_files_in_dir(partners_dir.as_str())?;
+            partner_files.retain(|file| {
+                fs::canonicalize(file).map_or(true, |partner_path| partner_path != agent_path)
+            });
+            if partner_files.len() < 2 {
+                println!("Need at least 2 partners to draw opponents from");
+                std::process::exit(1);
+            }
+            let mut partners: Vec<NeuralNetwork> = Vec::with_capacity(partner_files.len());
+            for partner_file in partner_files.iter() {
+                let mut partner = NeuralNetwork::new();
+                partner.load_from_file(partner_file.as_str())?;
+                partners.push(partner);
+            }
+            let win_rates = partner_robustness(&agent, &partners, games, &mut rand::thread_rng());
+            for (partner_file, win_rate) in partner_files.iter().zip(win_rates.iter()) {
+                println!("{}: {}", partner_file, win_rate);
+            }
+            let mean = win_rates.iter().sum::<f64>() / win_rates.len() as f64;
+            let variance =
+                win_rates.iter().map(|w| (w - mean).powi(2)).sum::<f64>() / win_rates.len() as f64;
+            println!("Partner Robustness: {}", mean);
+            println!("Partner Standard Deviation: {}", variance.sqrt());
+            Ok(())
+        })
+        .unwrap();
+
+    handle.join().unwrap().ok();
+}
+
+pub fn diversity_cli(dir: String, probes: usize, thread_count: usize) {
+    let handle = thread::Builder::new()
+        .spawn(move || -> std::io::Result<()> {
+            let files = network_files_in_dir(dir.as_str())?;
+            if files.len() < 2 {
+                println!("Need at least 2 networks to compare");
+                std::process::exit(1);
+            }
+            let mut networks: Vec<NeuralNetwork> = Vec::with_capacity(files.len());
+            for file in files.iter() {
+                let mut nn = NeuralNetwork::new();
+                nn.load_from_file(file.as_str())?;
+                networks.push(nn);
+            }
+            let networks: Vec<&NeuralNetwork> = networks.iter().collect();
+            let probes = probe_positions(probes, PROBE_SEED);
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(thread_count)
+                .build()
+                .unwrap();
+            let diversity = match pool.install(|| population_diversity(&networks, &probes)) {
+                Ok(diversity) => diversity,
+                Err(e) => {
+                    println!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            println!("Networks: {}", networks.len());
+            println!("Weight Distance: {}", diversity.weight_distance);
+            println!("Connection Distance: {}", diversity.connection_distance);
+            println!("Activation Distance: {}", diversity.activation_distance);
+            println!("Behavioural Distance: {}", diversity.behavioural_distance);
+            println!(
+                "Distinct Behaviours: {}/{}",
+                diversity.distinct_behaviours,
+                networks.len()
+            );
+            Ok(())
+        })
+        .unwrap();
+
+    handle.join().unwrap().ok();
+}
+
+pub fn puzzles_cli(
+    suite_file: String,
+    files: Vec<String>,
+    policy: ActionPolicy,
+    min_solved: Option<f64>,
+) {
+    let handle = thread::Builder::new()
+        .spawn(move || -> std::io::Result<()> {
+            let suite = match PuzzleSuite::load_from_file(suite_file.as_str()) {
+                Ok(suite) => suite,
+                Err(e) => {
+                    println!("Failed to load {}: {}", suite_file, e);
+                    std::process::exit(1);
+                }
+            };
+            if let Err(e) = suite.validate() {
+                println!("{}", e);
+                std::process::exit(1);
+            }
+            if files.is_empty() {
+                println!("Need at least 1 agent");
+                std::process::exit(1);
+            }
+            let mut below_minimum = false;
+            for file in files.iter() {
+                let agent = Agent::load(file.as_str())?;
+                let mut solved = 0;
+                for puzzle in suite.puzzles.iter() {
+                    let result = match puzzle.solve(&agent.seat(policy)) {
+                        Ok(result) => result,
+                        Err(e) => {
+                            println!("Puzzle {}: {}", puzzle.name, e);
+                            std::process::exit(1);
+                        }
+                    };
+                    if result.solved {
+                        solved += 1;
+                    }
+                    println!(
+                        "{} - {} - {} - {}",
+                        file,
+                        puzzle.name,
+                        if result.solved { "Solved" } else { "Failed" },
+                        result.answer
+                    );
+                }
+                let solved_rate = solved as f64 / suite.puzzles.len() as f64;
+                println!(
+                    "{} - Solved {}/{} ({:.1}%)",
+                    file,
+                    solved,
+                    suite.puzzles.len(),
+                    solved_rate * 100.0
+                );
+                if min_solved.is_some_and(|min_solved| solved_rate < min_solved) {
+                    below_minimum = true;
+                }
+            }
+            if below_minimum {
+                println!("Solved less than {} of the puzzles", min_solved.unwrap());
+                std::process::exit(1);
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    handle.join().unwrap().ok();
+}
+
+pub fn record_cli(
+    player_file: String,
+    opponent_file: Option<String>,
+    games: usize,
+    records_file: String,
+) {
+    let handle = thread::Builder::new()
+        .spawn(move || -> std::io::Result<()> {
+            let mut player = NeuralNetwork::new();
+            player.load_from_file(player_file.as_str())?;
+            let mut opponent = player.clone();
+            if let Some(opponent_file) = &opponent_file {
+                opponent.load_from_file(opponent_file.as_str())?;
+            }
+            for game in 1..games + 1 {
+                let seats = [
+                    player.seat(),
+                    opponent.seat(),
+                    player.seat(),
+                    opponent.seat(),
+                ];
+                let recorders: Vec<RecordingPlayer> = seats
+                    .iter()
+                    .map(|seat| RecordingPlayer::new(seat))
+                    .collect();
+                // without an opponent the player sits in every seat and every decision is recorded
+                match opponent_file {
+                    Some(_) => play_euchre(&recorders[0], &seats[1], &recorders[2], &seats[3]),
+                    None => play_euchre(&recorders[0], &recorders[1], &recorders[2], &recorders[3]),
+                };
+                let records: Vec<_> = recorders
+                    .iter()
+                    .flat_map(|recorder| recorder.take_records())
+                    .collect();
+                if let Err(e) = append_records(records_file.as_str(), &records) {
+                    println!("Failed to record decisions to {}: {}", records_file, e);
+                    std::process::exit(1);
+                }
+                println!(
+                    "Game {}/{} - {} Decisions Recorded",
+                    game,
+                    games,
+                    records.len()
+                );
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    handle.join().unwrap().ok();
+}
+
+pub fn train_cli(
+    records_files: Vec<String>,
+    initial_file: Option<String>,
+    out_file: String,
+    config: TrainingConfig,
+) {
+    let handle = thread::Builder::new()
+        .spawn(move || -> std::io::Result<()> {
+            let mut records = Vec::new();
+            for records_file in records_files.iter() {
+                match load_records(records_file.as_str()) {
+                    Ok(mut file_records) => records.append(&mut file_records),
+                    Err(e) => {
+                        println!("Failed to load records {}: {}", records_file, e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if records.is_empty() {
+                println!("No records to train on");
+                std::process::exit(1);
+            }
+            let mut nn = NeuralNetwork::new();
+            match &initial_file {
+                Some(initial_file) => nn.load_from_file(initial_file.as_str())?,
+                None => nn.init(&InitConfig::default()),
+            }
+            if nn.is_recurrent() {
+                println!("Gradient training doesn't support recurrent networks");
+                std::process::exit(1);
+            }
+            train(&mut nn, &records, &config, &mut rand::thread_rng());
+            nn.save_to_file(out_file.as_str())?;
+            Ok(())
+        })
+        .unwrap();
+
+    handle.join().unwrap().ok();
+}
+
+pub fn train_rl_cli(initial_file: Option<String>, out_file: String, config: ReinforcementConfig) {
+    let handle = thread::Builder::new()
+        .spawn(move || -> std::io::Result<()> {
+            let mut nn = NeuralNetwork::new();
+            match &initial_file {
+                Some(initial_file) => nn.load_from_file(initial_file.as_str())?,
+                None => nn.init(&InitConfig::default()),
+            }
+            if nn.is_recurrent() {
+                println!("Gradient training doesn't support recurrent networks");
+                std::process::exit(1);
+            }
+            train_reinforcement(&mut nn, &config);
+            nn.save_to_file(out_file.as_str())?;
+            Ok(())
+        })
+        .unwrap();
+
+    handle.join().unwrap().ok();
+}
+
+// The decisions a network makes playing itself
+fn self_play_records(nn: &NeuralNetwork, games: usize) -> Vec<DecisionRecord> {
+    let mut records = Vec::new();
+    for _ in 0..games {
+        let seats = [nn.seat(), nn.seat(), nn.seat(), nn.seat()];
+        let recorders: Vec<RecordingPlayer> = seats
+            .iter()
+            .map(|seat| RecordingPlayer::new(seat))
+            .collect();
+        play_euchre(&recorders[0], &recorders[1], &recorders[2], &recorders[3]);
+        for recorder in recorders.iter() {
+            records.append(&mut recorder.take_records());
+        }
+    }
+    records
+}
+
+pub fn invariance_cli(file: String, games: usize) {
+    let handle = thread::Builder::new()
+        .spawn(move || -> std::io::Result<()> {
+            let mut nn = NeuralNetwork::new();
+            nn.load_from_file(file.as_str())?;
+            if nn.is_recurrent() {
+                println!("Invariance testing doesn't support recurrent networks");
+                std::process::exit(1);
+            }
+            let records = self_play_records(&nn, games);
+            let rates = invariance(&nn, &records);
+            for (permutation, rate) in rates.iter() {
+                println!("Permutation {} - Changed: {}", permutation.name(), rate);
+            }
+            println!(
+                "Decisions: {}, Mean Changed: {}",
+                records.len(),
+                rates.iter().map(|(_, rate)| rate).sum::<f64>() / rates.len() as f64
+            );
+            Ok(())
+        })
+        .unwrap();
+
+    handle.join().unwrap().ok();
+}
+
+pub fn benchmark_cli(file: String, games: usize, batch_size: usize) {
+    let handle = thread::Builder::new()
+        .spawn(move || -> std::io::Result<()> {
+            let mut nn = NeuralNetwork::new();
+            nn.load_from_file(file.as_str())?;
+            if nn.is_recurrent() {
+                println!("Benchmarking doesn't support recurrent networks");
+                std::process::exit(1);
+            }
+            let inputs: Vec<_> = self_play_records(&nn, games)
+                .iter()
+                .map(|record| record.inputs())
+                .collect();
+            println!("Benchmarking on {} decisions", inputs.len());
+            benchmark(&nn, &inputs, batch_size);
+            Ok(())
+        })
+        .unwrap();
+
+    handle.join().unwrap().ok();
+}
+
+pub fn quantize_cli(file: String, out_file: String, precision: Precision) {
+    let handle = thread::Builder::new()
+        .spawn(move || -> std::io::Result<()> {
+            let mut nn = NeuralNetwork::new();
+            nn.load_from_file(file.as_str())?;
+            let quantized = match QuantizedNetwork::from_network(&nn, precision) {
+                Ok(quantized) => quantized,
+                Err(e) => {
+                    println!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            quantized.save_to_file(out_file.as_str())?;
+            println!(
+                "Saved {:?} network to {} - {} bytes, from {} bytes",
+                quantized.precision(),
+                out_file,
+                fs::metadata(&out_file)?.len(),
+                fs::metadata(&file)?.len()
+            );
+            Ok(())
+        })
+        .unwrap();
+
+    handle.join().unwrap().ok();
+}
+
+pub fn quantized_accuracy_cli(file: String, quantized_file: String, games: usize) {
+    let handle = thread::Builder::new()
+        .spawn(move || -> std::io::Result<()> {
+            let mut nn = NeuralNetwork::new();
+            nn.load_from_file(file.as_str())?;
+            if nn.is_recurrent() {
+                println!("Recurrent networks can't be quantised");
+                std::process::exit(1);
+            }
+            let quantized = QuantizedNetwork::load_from_file(quantized_file.as_str())?;
+            let records = self_play_records(&nn, games);
+            let (different, max_difference) = compare_quantized(&nn, &quantized, &records);
+            println!(
+                "Decisions: {}, Different Actions: {}, Max Output Difference: {}",
+                records.len(),
+                different,
+                max_difference
+            );
+            Ok(())
+        })
+        .unwrap();
+
+    handle.join().unwrap().ok();
+}
@@ -1 +1,2 @@
 pub mod helpers;
+pub mod options;
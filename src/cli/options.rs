@@ -0,0 +1,268 @@
+use structopt::StructOpt;
+
+use crate::euchre::bots::Bot;
+use crate::organism::{
+    config::{EvolutionConfig, MutationConfig},
+    curriculum::Curriculum,
+    encoding::InputEncoding,
+    evaluation::PartnerMode,
+    metrics::MetricsFormat,
+    mutation::MutationDistribution,
+    novelty::SearchMode,
+    policy::ActionPolicy,
+    reinforcement::{ReinforcementConfig, RewardMode},
+    tournament::TournamentFormat,
+    training::TrainingConfig,
+};
+
+#[derive(Debug, StructOpt)]
+pub struct EvolutionOptions {
+    #[structopt(short, long)]
+    population_size: Option<usize>,
+    #[structopt(short, long)]
+    generations: Option<usize>,
+    #[structopt(short, long)]
+    thread_count: Option<usize>,
+    #[structopt(
+        long,
+        use_delimiter = true,
+        help = "Comma separated hidden layer widths"
+    )]
+    hidden_layers: Option<Vec<usize>>,
+    #[structopt(long, help = "Carry a hidden state across decisions within a round")]
+    recurrent: bool,
+    #[structopt(long, help = "absolute or suit_relative")]
+    encoding: Option<InputEncoding>,
+    #[structopt(long)]
+    init_weight_range: Option<f64>,
+    #[structopt(long)]
+    init_bias_range: Option<f64>,
+    #[structopt(long)]
+    connection_probability: Option<f64>,
+    #[structopt(long)]
+    games_per_match: Option<usize>,
+    #[structopt(long)]
+    evaluation_rounds: Option<usize>,
+    #[structopt(long, help = "self_partner or rotating_partners")]
+    partner_mode: Option<PartnerMode>,
+    #[structopt(
+        long,
+        help = "argmax, softmax:<temperature> or epsilon_greedy:<epsilon> during matches"
+    )]
+    policy: Option<ActionPolicy>,
+    #[structopt(long)]
+    breeding_fraction: Option<f64>,
+    #[structopt(long)]
+    survivor_fraction: Option<f64>,
+    #[structopt(long, help = "objective, novelty:<weight> or map_elites:<bins>")]
+    search: Option<SearchMode>,
+    #[structopt(
+        long,
+        help = "full_game, trick_play:<rounds>, bidding:<rounds> or opponents:<promotion win rate>"
+    )]
+    curriculum: Option<Curriculum>,
+    #[structopt(
+        long,
+        help = "single_elimination, double_elimination, swiss:<rounds> or round_robin:<finalists>"
+    )]
+    tournament: Option<TournamentFormat>,
+    #[structopt(long)]
+    tournament_games_per_match: Option<usize>,
+    #[structopt(long, help = "Save the top k organisms of the tournament")]
+    top_k: Option<usize>,
+    #[structopt(long, help = "jsonl or csv")]
+    metrics_format: Option<MetricsFormat>,
+    #[structopt(
+        long,
+        use_delimiter = true,
+        help = "Bots every organism plays each generation, random or greedy, comma separated"
+    )]
+    baselines: Option<Vec<Bot>>,
+    #[structopt(long, help = "Games against each baseline per organism")]
+    baseline_games: Option<usize>,
+    #[structopt(flatten)]
+    mutation: MutationOptions,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct MutationOptions {
+    #[structopt(long)]
+    mutation_rate: Option<f64>,
+    #[structopt(long)]
+    mutation_magnitude: Option<f64>,
+    #[structopt(long, help = "uniform or gaussian")]
+    mutation_distribution: Option<MutationDistribution>,
+    #[structopt(
+        long,
+        help = "Evolve per node mutation step sizes stored in the genome"
+    )]
+    self_adaptive_mutation: bool,
+}
+
+impl EvolutionOptions {
+    pub fn apply(&self, config: &mut EvolutionConfig) {
+        if let Some(population_size) = self.population_size {
+            config.population_size = population_size;
+        }
+        if let Some(generations) = self.generations {
+            config.generations = generations;
+        }
+        if let Some(thread_count) = self.thread_count {
+            config.thread_count = thread_count;
+        }
+        if let Some(hidden_layers) = &self.hidden_layers {
+            config.network.hidden_layers = hidden_layers.clone();
+        }
+        if self.recurrent {
+            config.network.recurrent = true;
+        }
+        if let Some(encoding) = self.encoding {
+            config.network.encoding = encoding;
+        }
+        if let Some(init_weight_range) = self.init_weight_range {
+            config.init.weight_range = init_weight_range;
+        }
+        if let Some(init_bias_range) = self.init_bias_range {
+            config.init.bias_range = init_bias_range;
+        }
+        if let Some(connection_probability) = self.connection_probability {
+            config.init.connection_probability = connection_probability;
+        }
+        if let Some(games_per_match) = self.games_per_match {
+            config.evaluation.games_per_match = games_per_match;
+        }
+        if let Some(evaluation_rounds) = self.evaluation_rounds {
+            config.evaluation.rounds = evaluation_rounds;
+        }
+        if let Some(partner_mode) = self.partner_mode {
+            config.evaluation.partner_mode = partner_mode;
+        }
+        if let Some(policy) = self.policy {
+            config.evaluation.policy = policy;
+        }
+        if let Some(breeding_fraction) = self.breeding_fraction {
+            config.selection.breeding_fraction = breeding_fraction;
+        }
+        if let Some(survivor_fraction) = self.survivor_fraction {
+            config.selection.survivor_fraction = survivor_fraction;
+        }
+        if let Some(search) = self.search {
+            config.search = search;
+        }
+        if let Some(curriculum) = self.curriculum {
+            config.curriculum = curriculum;
+        }
+        if let Some(tournament) = self.tournament {
+            config.tournament.format = tournament;
+        }
+        if let Some(tournament_games_per_match) = self.tournament_games_per_match {
+            config.tournament.games_per_match = tournament_games_per_match;
+        }
+        if let Some(top_k) = self.top_k {
+            config.tournament.top_k = top_k;
+        }
+        if let Some(metrics_format) = self.metrics_format {
+            config.metrics.format = metrics_format;
+        }
+        if let Some(baselines) = &self.baselines {
+            config.metrics.baselines = baselines.clone();
+        }
+        if let Some(baseline_games) = self.baseline_games {
+            config.metrics.baseline_games = baseline_games;
+        }
+        self.mutation.apply(&mut config.mutation);
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub struct TrainingOptions {
+    #[structopt(long)]
+    epochs: Option<usize>,
+    #[structopt(long)]
+    learning_rate: Option<f64>,
+    #[structopt(long)]
+    batch_size: Option<usize>,
+    #[structopt(long, help = "Fraction of records held out to measure accuracy on")]
+    validation_fraction: Option<f64>,
+    #[structopt(
+        long,
+        help = "Also train on every suit relabelling of the training records"
+    )]
+    augment: bool,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ReinforcementOptions {
+    #[structopt(short, long, help = "Self-play games in total")]
+    games: Option<usize>,
+    #[structopt(long)]
+    games_per_update: Option<usize>,
+    #[structopt(long)]
+    learning_rate: Option<f64>,
+    #[structopt(long, help = "Softmax temperature of the sampled policy")]
+    temperature: Option<f64>,
+    #[structopt(long, help = "game or round")]
+    reward: Option<RewardMode>,
+    #[structopt(short, long)]
+    thread_count: Option<usize>,
+}
+
+impl MutationOptions {
+    pub fn apply(&self, config: &mut MutationConfig) {
+        if let Some(mutation_rate) = self.mutation_rate {
+            config.rate = mutation_rate;
+        }
+        if let Some(mutation_magnitude) = self.mutation_magnitude {
+            config.magnitude = mutation_magnitude;
+        }
+        if let Some(mutation_distribution) = self.mutation_distribution {
+            config.distribution = mutation_distribution;
+        }
+        if self.self_adaptive_mutation {
+            config.self_adaptive = true;
+        }
+    }
+}
+
+impl TrainingOptions {
+    pub fn apply(&self, config: &mut TrainingConfig) {
+        if let Some(epochs) = self.epochs {
+            config.epochs = epochs;
+        }
+        if let Some(learning_rate) = self.learning_rate {
+            config.learning_rate = learning_rate;
+        }
+        if let Some(batch_size) = self.batch_size {
+            config.batch_size = batch_size;
+        }
+        if let Some(validation_fraction) = self.validation_fraction {
+            config.validation_fraction = validation_fraction;
+        }
+        if self.augment {
+            config.augment = true;
+        }
+    }
+}
+
+impl ReinforcementOptions {
+    pub fn apply(&self, config: &mut ReinforcementConfig) {
+        if let Some(games) = self.games {
+            config.games = games;
+        }
+        if let Some(games_per_update) = self.games_per_update {
+            config.games_per_update = games_per_update;
+        }
+        if let Some(learning_rate) = self.learning_rate {
+            config.learning_rate = learning_rate;
+        }
+        if let Some(temperature) = self.temperature {
+            config.temperature = temperature;
+        }
+        if let Some(reward) = self.reward {
+            config.reward = reward;
+        }
+        if let Some(thread_count) = self.thread_count {
+            config.thread_count = thread_count;
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::str::FromStr;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use strum::EnumCount;
+
+use crate::organism::neural_network::{AvailableActions, NeuralNetworkInput};
+
+use super::enums::{ActionIndex, Rank, StateIndex, Suit};
+use super::player::Player;
+
+const CARD_COUNT: usize = Suit::COUNT * Rank::COUNT;
+// trump cards in hand, counting the left bower, needed to name a suit
+const MAKE_THRESHOLD: usize = 3;
+
+// Fixed strategies to measure networks against
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Bot {
+    // a uniformly random available action
+    Random,
+    // names trump with three or more trump in hand, never goes alone, discards its weakest card
+    // and always plays its strongest available card
+    Greedy,
+}
+
+impl FromStr for Bot {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "random" => Ok(Bot::Random),
+            "greedy" => Ok(Bot::Greedy),
+            _ => Err(format!("unknown bot {}", s)),
+        }
+    }
+}
+
+impl Bot {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Bot::Random => "random",
+            Bot::Greedy => "greedy",
+        }
+    }
+}
+
+// The card's suit with the left bower counted as trump
+fn effective_suit(card: usize, trump: usize) -> usize {
+    let (suit, rank) = (card / Rank::COUNT, card % Rank::COUNT);
+    // spades pair with clubs and hearts with diamonds
+    if rank == Rank::Jack as usize && suit == Suit::COUNT - 1 - trump {
+        return trump;
+    }
+    suit
+}
+
+// Right bower, left bower, then the rest of trump, then off suit cards by rank
+fn strength(card: usize, trump: Option<usize>) -> usize {
+    let rank = card % Rank::COUNT;
+    match trump {
+        Some(trump) if card == trump * Rank::COUNT + Rank::Jack as usize => 3 * Rank::COUNT + 1,
+        Some(trump) if effective_suit(card, trump) == trump => {
+            if rank == Rank::Jack as usize {
+                3 * Rank::COUNT
+            } else {
+                2 * Rank::COUNT + rank
+            }
+        }
+        _ => rank,
+    }
+}
+
+fn hand(inputs: &NeuralNetworkInput) -> Vec<usize> {
+    (0..CARD_COUNT)
+        .filter(|&card| inputs[StateIndex::HandSpadeNine as usize + card] != 0.0)
+        .collect()
+}
+
+// Known trump, or the upcard's suit while it could still be ordered up or is being picked up
+fn trump(inputs: &NeuralNetworkInput) -> Option<usize> {
+    (0..Suit::COUNT)
+        .find(|&suit| inputs[StateIndex::TrumpSuitSpade as usize + suit] != 0.0)
+        .or_else(|| {
+            (0..CARD_COUNT)
+                .find(|&card| inputs[StateIndex::UpcardSpadeNine as usize + card] != 0.0)
+                .map(|card| card / Rank::COUNT)
+        })
+}
+
+fn trump_count(hand: &[usize], trump: usize) -> usize {
+    hand.iter()
+        .filter(|&&card| effective_suit(card, trump) == trump)
+        .count()
+}
+
+impl Bot {
+    fn greedy_action(
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex {
+        let hand = hand(inputs);
+        let trump = trump(inputs);
+        if available_actions[ActionIndex::PassUpcard as usize] {
+            let upcard_suit = trump.expect("no upcard while bidding");
+            if trump_count(&hand, upcard_suit) >= MAKE_THRESHOLD {
+                return ActionIndex::MakeUpcard;
+            }
+            return ActionIndex::PassUpcard;
+        }
+        let make_suit = ActionIndex::MakeSuitSpade as usize;
+        if available_actions[make_suit..make_suit + Suit::COUNT]
+            .iter()
+            .any(|&available| available)
+        {
+            let best = (0..Suit::COUNT)
+                .filter(|&suit| available_actions[make_suit + suit])
+                .max_by_key(|&suit| trump_count(&hand, suit))
+                .unwrap();
+            if trump_count(&hand, best) >= MAKE_THRESHOLD
+                || !available_actions[ActionIndex::PassSuit as usize]
+            {
+                return ActionIndex::from_usize(make_suit + best);
+            }
+            return ActionIndex::PassSuit;
+        }
+        let discard = ActionIndex::DiscardSpadeNine as usize;
+        if available_actions[discard..discard + CARD_COUNT]
+            .iter()
+            .any(|&available| available)
+        {
+            let weakest = (0..CARD_COUNT)
+                .filter(|&card| available_actions[discard + card])
+                .min_by_key(|&card| strength(card, trump))
+                .unwrap();
+            return ActionIndex::from_usize(discard + weakest);
+        }
+        let strongest = (0..CARD_COUNT)
+            .filter(|&card| available_actions[ActionIndex::PlaySpadeNine as usize + card])
+            .max_by_key(|&card| strength(card, trump))
+            .expect("No available actions!");
+        ActionIndex::from_usize(ActionIndex::PlaySpadeNine as usize + strongest)
+    }
+}
+
+impl Player for Bot {
+    fn get_action(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex {
+        match self {
+            Bot::Random => {
+                let available: Vec<usize> = (0..ActionIndex::COUNT)
+                    .filter(|&i| available_actions[i])
+                    .collect();
+                ActionIndex::from_usize(available[rand::thread_rng().gen_range(0..available.len())])
+            }
+            Bot::Greedy => Bot::greedy_action(inputs, available_actions),
+        }
+    }
+}
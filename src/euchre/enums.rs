@@ -23,6 +23,7 @@ pub enum LeadRelativePosition {
     Ally,
     Right,
 }
+#[derive(Clone, Copy)]
 pub enum DealerRelativePosition {
     Dealer,
     Left,
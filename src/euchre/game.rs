@@ -1,17 +1,77 @@
 use crate::organism::neural_network::*;
 
-use super::{constants::*, enums::*, game_helpers::*, neural_network_helpers::*, types::*};
+use super::{
+    constants::*, enums::*, game_helpers::*, neural_network_helpers::*, player::Player, types::*,
+};
 use rand::Rng;
 use strum::EnumCount;
 
+// What happened in a round, with seats indexed North, East, South, West and teams North/South,
+// East/West
+#[derive(Debug, Clone, Default)]
+pub struct RoundSummary {
+    pub dealer: usize,
+    // seat that named trump, none if every seat passed twice
+    pub maker: Option<usize>,
+    // trump was the upcard ordered up rather than a suit called in the second round of bidding
+    pub ordered_up: bool,
+    pub alone: bool,
+    pub tricks: [u8; 2],
+    pub points: [u8; 2],
+    // seat leading each of the first four tricks and whether it led trump, the fifth lead is
+    // forced
+    pub leads: Vec<(usize, bool)>,
+}
+
+impl RoundSummary {
+    // The same round with every seat moved offset places to the left, swapping the teams when
+    // offset is odd
+    pub fn rotated(&self, offset: usize) -> RoundSummary {
+        let seat = |s: usize| (s + offset) % 4;
+        let teams = |values: [u8; 2]| [values[offset % 2], values[(offset + 1) % 2]];
+        RoundSummary {
+            dealer: seat(self.dealer),
+            maker: self.maker.map(seat),
+            ordered_up: self.ordered_up,
+            alone: self.alone,
+            tricks: teams(self.tricks),
+            points: teams(self.points),
+            leads: self
+                .leads
+                .iter()
+                .map(|&(s, trump)| (seat(s), trump))
+                .collect(),
+        }
+    }
+}
+
 pub fn play_euchre(
-    north_player: &NeuralNetwork,
-    east_player: &NeuralNetwork,
-    south_player: &NeuralNetwork,
-    west_player: &NeuralNetwork,
+    north_player: &dyn Player,
+    east_player: &dyn Player,
+    south_player: &dyn Player,
+    west_player: &dyn Player,
 ) -> Team {
-    let mut rng = rand::thread_rng();
+    play_euchre_with_summaries(
+        north_player,
+        east_player,
+        south_player,
+        west_player,
+        &mut rand::thread_rng(),
+        &mut |_| {},
+    )
+}
 
+// Like play_euchre, dealing from rng and handing each round's summary to on_round as it finishes.
+// The first dealer and the cards only depend on rng, so the same seed deals the same cards to the
+// same seats
+pub fn play_euchre_with_summaries<R: Rng>(
+    north_player: &dyn Player,
+    east_player: &dyn Player,
+    south_player: &dyn Player,
+    west_player: &dyn Player,
+    rng: &mut R,
+    on_round: &mut dyn FnMut(&RoundSummary),
+) -> Team {
     let mut north_south_score: u8 = 0;
     let mut east_west_score: u8 = 0;
 
@@ -28,83 +88,104 @@ pub fn play_euchre(
         set_score(south_input, &north_south_score, &east_west_score);
         set_score(west_input, &east_west_score, &north_south_score);
 
-        let (dealer_score_delta, other_score_delta) = match dealer {
-            &Position::North => {
+        let mut summary = RoundSummary {
+            dealer: seat_index(dealer),
+            ..Default::default()
+        };
+        let (dealer_hand, position_1_hand, position_2_hand, position_3_hand, upcard) = deal(rng);
+        let hands = [
+            dealer_hand,
+            position_1_hand,
+            position_2_hand,
+            position_3_hand,
+        ];
+        let (dealer_score_delta, other_score_delta) = match *dealer {
+            Position::North => {
                 set_dealer(north_input, &RelativePosition::Myself);
                 set_dealer(east_input, &RelativePosition::Right);
                 set_dealer(south_input, &RelativePosition::Ally);
                 set_dealer(west_input, &RelativePosition::Left);
+                let players = [north_player, east_player, south_player, west_player];
                 run_round(
-                    north_player,
-                    east_player,
-                    south_player,
-                    west_player,
+                    players,
+                    players,
                     north_input,
                     east_input,
                     south_input,
                     west_input,
+                    hands,
+                    upcard,
+                    &mut summary,
                 )
             }
-            &Position::East => {
+            Position::East => {
                 set_dealer(north_input, &RelativePosition::Left);
                 set_dealer(east_input, &RelativePosition::Myself);
                 set_dealer(south_input, &RelativePosition::Right);
                 set_dealer(west_input, &RelativePosition::Ally);
+                let players = [east_player, south_player, west_player, north_player];
                 run_round(
-                    east_player,
-                    south_player,
-                    west_player,
-                    north_player,
+                    players,
+                    players,
                     east_input,
                     south_input,
                     west_input,
                     north_input,
+                    hands,
+                    upcard,
+                    &mut summary,
                 )
             }
-            &Position::South => {
+            Position::South => {
                 set_dealer(north_input, &RelativePosition::Ally);
                 set_dealer(east_input, &RelativePosition::Left);
                 set_dealer(south_input, &RelativePosition::Myself);
                 set_dealer(west_input, &RelativePosition::Right);
+                let players = [south_player, west_player, north_player, east_player];
                 run_round(
-                    south_player,
-                    west_player,
-                    north_player,
-                    east_player,
+                    players,
+                    players,
                     south_input,
                     west_input,
                     north_input,
                     east_input,
+                    hands,
+                    upcard,
+                    &mut summary,
                 )
             }
-            &Position::West => {
+            Position::West => {
                 set_dealer(north_input, &RelativePosition::Right);
                 set_dealer(east_input, &RelativePosition::Ally);
                 set_dealer(south_input, &RelativePosition::Left);
                 set_dealer(west_input, &RelativePosition::Myself);
+                let players = [west_player, north_player, east_player, south_player];
                 run_round(
-                    west_player,
-                    north_player,
-                    east_player,
-                    south_player,
+                    players,
+                    players,
                     west_input,
                     north_input,
                     east_input,
                     south_input,
+                    hands,
+                    upcard,
+                    &mut summary,
                 )
             }
         };
 
-        match dealer {
-            &Position::North | &Position::South => {
-                north_south_score += dealer_score_delta;
-                east_west_score += other_score_delta;
-            }
-            &Position::East | &Position::West => {
-                north_south_score += other_score_delta;
-                east_west_score += dealer_score_delta;
-            }
+        let (north_south_score_delta, east_west_score_delta) = match dealer {
+            &Position::North | &Position::South => (dealer_score_delta, other_score_delta),
+            &Position::East | &Position::West => (other_score_delta, dealer_score_delta),
         };
+        north_south_score += north_south_score_delta;
+        east_west_score += east_west_score_delta;
+        north_player.end_round(north_south_score_delta, east_west_score_delta);
+        east_player.end_round(east_west_score_delta, north_south_score_delta);
+        south_player.end_round(north_south_score_delta, east_west_score_delta);
+        west_player.end_round(east_west_score_delta, north_south_score_delta);
+        summary.points = [north_south_score_delta, east_west_score_delta];
+        on_round(&summary);
 
         dealer = left_player(dealer);
     }
@@ -115,18 +196,87 @@ pub fn play_euchre(
     }
 }
 
+// A single round at 0-0 dealt from rng, with seats indexed from the dealer and going left.
+// Bidders name trump while players discard and play the tricks, so either half of a round can be
+// scripted while the other is played by the seats being measured. Points in the summary are
+// indexed like the teams, the dealer's team first
+pub fn play_round<R: Rng>(
+    bidders: [&dyn Player; 4],
+    players: [&dyn Player; 4],
+    rng: &mut R,
+) -> RoundSummary {
+    let (dealer_hand, position_1_hand, position_2_hand, position_3_hand, upcard) = deal(rng);
+    play_dealt_round(
+        bidders,
+        players,
+        [
+            dealer_hand,
+            position_1_hand,
+            position_2_hand,
+            position_3_hand,
+        ],
+        upcard,
+        [0, 0],
+    )
+}
+
+// Like play_round with the hands and upcard given, and the score before the round for the
+// dealer's team and the other team
+pub fn play_dealt_round(
+    bidders: [&dyn Player; 4],
+    players: [&dyn Player; 4],
+    hands: [[Option<Card>; 6]; 4],
+    upcard: Card,
+    score: [u8; 2],
+) -> RoundSummary {
+    let mut inputs: [NeuralNetworkInput; 4] = [[0.0; StateIndex::COUNT]; 4];
+    for (i, (input, dealer)) in inputs
+        .iter_mut()
+        .zip([
+            RelativePosition::Myself,
+            RelativePosition::Right,
+            RelativePosition::Ally,
+            RelativePosition::Left,
+        ])
+        .enumerate()
+    {
+        set_score(input, &score[i % 2], &score[(i + 1) % 2]);
+        set_dealer(input, &dealer);
+    }
+    let [dealer_input, position_1_input, position_2_input, position_3_input] = &mut inputs;
+    let mut summary = RoundSummary::default();
+    let (dealer_score_delta, other_score_delta) = run_round(
+        bidders,
+        players,
+        dealer_input,
+        position_1_input,
+        position_2_input,
+        position_3_input,
+        hands,
+        upcard,
+        &mut summary,
+    );
+    summary.points = [dealer_score_delta, other_score_delta];
+    summary
+}
+
+// Bidders, players, inputs and hands are in dealer relative order, starting with the dealer,
+// bidders name trump while players discard and play the tricks
+#[allow(clippy::too_many_arguments)]
 fn run_round(
-    dealer_player: &NeuralNetwork,
-    position_1_player: &NeuralNetwork,
-    position_2_player: &NeuralNetwork,
-    position_3_player: &NeuralNetwork,
+    bidders: [&dyn Player; 4],
+    players: [&dyn Player; 4],
     dealer_input: &mut NeuralNetworkInput,
     position_1_input: &mut NeuralNetworkInput,
     position_2_input: &mut NeuralNetworkInput,
     position_3_input: &mut NeuralNetworkInput,
+    hands: [[Option<Card>; 6]; 4],
+    upcard: Card,
+    summary: &mut RoundSummary,
 ) -> (u8, u8) {
-    let (mut dealer_hand, mut position_1_hand, mut position_2_hand, mut position_3_hand, upcard) =
-        deal();
+    let [dealer_bidder, position_1_bidder, position_2_bidder, position_3_bidder] = bidders;
+    let [dealer_player, position_1_player, position_2_player, position_3_player] = players;
+    let [mut dealer_hand, mut position_1_hand, mut position_2_hand, mut position_3_hand] = hands;
     set_hand(dealer_input, &dealer_hand);
     set_hand(position_1_input, &position_1_hand);
     set_hand(position_2_input, &position_2_hand);
@@ -142,10 +292,10 @@ fn run_round(
         mut skip_position_2,
         mut skip_position_3,
     ) = run_bid_upcard(
-        dealer_player,
-        position_1_player,
-        position_2_player,
-        position_3_player,
+        dealer_bidder,
+        position_1_bidder,
+        position_2_bidder,
+        position_3_bidder,
         dealer_input,
         position_1_input,
         position_2_input,
@@ -165,10 +315,10 @@ fn run_round(
             skip_position_2,
             skip_position_3,
         ) = run_bid_suit(
-            dealer_player,
-            position_1_player,
-            position_2_player,
-            position_3_player,
+            dealer_bidder,
+            position_1_bidder,
+            position_2_bidder,
+            position_3_bidder,
             dealer_input,
             position_1_input,
             position_2_input,
@@ -179,6 +329,11 @@ fn run_round(
     if making_team.is_none() || trump_suit.is_none() {
         return (0, 0);
     }
+    if let Some((position, ordered_up)) = get_maker(dealer_input) {
+        summary.maker = Some((summary.dealer + position) % 4);
+        summary.ordered_up = ordered_up;
+    }
+    summary.alone = skip_dealer || skip_position_1 || skip_position_2 || skip_position_3;
 
     set_trump_suit(dealer_input, &trump_suit.unwrap());
     set_trump_suit(position_1_input, &trump_suit.unwrap());
@@ -203,8 +358,33 @@ fn run_round(
         &skip_position_2,
         &skip_position_3,
         &trump_suit.unwrap(),
+        summary,
     );
+    summary.tricks[summary.dealer % 2] = dealer_team_tricks;
+    summary.tricks[(summary.dealer + 1) % 2] = other_team_tricks;
+
+    score_round(
+        making_team,
+        dealer_team_tricks,
+        other_team_tricks,
+        skip_dealer,
+        skip_position_1,
+        skip_position_2,
+        skip_position_3,
+    )
+}
 
+// Points for the dealer's team and the other team given who made trump, the tricks each team took
+// and which seat, if any, sat out of a loner
+pub fn score_round(
+    making_team: Option<RelativeTeam>,
+    dealer_team_tricks: u8,
+    other_team_tricks: u8,
+    skip_dealer: bool,
+    skip_position_1: bool,
+    skip_position_2: bool,
+    skip_position_3: bool,
+) -> (u8, u8) {
     match (
         making_team,
         dealer_team_tricks,
@@ -215,33 +395,36 @@ fn run_round(
         skip_position_3,
     ) {
         (Some(RelativeTeam::Dealer), 5, _, true, _, _, _)
-        | (Some(RelativeTeam::Dealer), 5, _, _, _, true, _) => return (4, 0),
+        | (Some(RelativeTeam::Dealer), 5, _, _, _, true, _) => (4, 0),
         (Some(RelativeTeam::Other), _, 5, _, true, _, _)
-        | (Some(RelativeTeam::Other), _, 5, _, _, _, true) => return (0, 4),
+        | (Some(RelativeTeam::Other), _, 5, _, _, _, true) => (0, 4),
 
         (Some(RelativeTeam::Dealer), 5, _, false, _, false, _)
         | (Some(RelativeTeam::Other), _, 2, _, _, _, _)
         | (Some(RelativeTeam::Other), _, 1, _, _, _, _)
-        | (Some(RelativeTeam::Other), _, 0, _, _, _, _) => return (2, 0),
+        | (Some(RelativeTeam::Other), _, 0, _, _, _, _) => (2, 0),
 
         (Some(RelativeTeam::Other), _, 5, _, false, _, false)
         | (Some(RelativeTeam::Dealer), 2, _, _, _, _, _)
         | (Some(RelativeTeam::Dealer), 1, _, _, _, _, _)
-        | (Some(RelativeTeam::Dealer), 0, _, _, _, _, _) => return (0, 2),
+        | (Some(RelativeTeam::Dealer), 0, _, _, _, _, _) => (0, 2),
 
         (Some(RelativeTeam::Dealer), 4, _, _, _, _, _)
-        | (Some(RelativeTeam::Dealer), 3, _, _, _, _, _) => return (1, 0),
+        | (Some(RelativeTeam::Dealer), 3, _, _, _, _, _) => (1, 0),
         (Some(RelativeTeam::Other), _, 4, _, _, _, _)
-        | (Some(RelativeTeam::Other), _, 3, _, _, _, _) => return (0, 1),
+        | (Some(RelativeTeam::Other), _, 3, _, _, _, _) => (0, 1),
         _ => panic!("failed to score round"),
     }
 }
 
-fn run_bid_upcard(
-    dealer_player: &NeuralNetwork,
-    position_1_player: &NeuralNetwork,
-    position_2_player: &NeuralNetwork,
-    position_3_player: &NeuralNetwork,
+// The first round of bidding on the upcard starting left of the dealer, returns the team that
+// ordered it up, if any, and which seat sits out of a loner
+#[allow(clippy::too_many_arguments)]
+pub fn run_bid_upcard(
+    dealer_player: &dyn Player,
+    position_1_player: &dyn Player,
+    position_2_player: &dyn Player,
+    position_3_player: &dyn Player,
     dealer_input: &mut NeuralNetworkInput,
     position_1_input: &mut NeuralNetworkInput,
     position_2_input: &mut NeuralNetworkInput,
@@ -299,13 +482,13 @@ fn run_bid_upcard(
 }
 
 fn get_bid_upcard_action(
-    myself: &NeuralNetwork,
+    myself: &dyn Player,
     myself_input: &mut NeuralNetworkInput,
     left_input: &mut NeuralNetworkInput,
     ally_input: &mut NeuralNetworkInput,
     right_input: &mut NeuralNetworkInput,
 ) -> Option<(bool, bool)> {
-    match myself.get_action(&myself_input, &BID_UPCARD_AVAILABLE_ACTIONS) {
+    match myself.get_action(myself_input, &BID_UPCARD_AVAILABLE_ACTIONS) {
         ActionIndex::MakeUpcard => {
             set_bid_upcard(
                 myself_input,
@@ -327,7 +510,7 @@ fn get_bid_upcard_action(
                 &RelativePosition::Left,
                 &ActionIndex::MakeUpcard,
             );
-            return Some((true, false));
+            Some((true, false))
         }
         ActionIndex::MakeUpcardAlone => {
             set_bid_upcard(
@@ -350,7 +533,7 @@ fn get_bid_upcard_action(
                 &RelativePosition::Left,
                 &ActionIndex::MakeUpcardAlone,
             );
-            return Some((true, true));
+            Some((true, true))
         }
         ActionIndex::PassUpcard => {
             set_bid_upcard(
@@ -373,14 +556,15 @@ fn get_bid_upcard_action(
                 &RelativePosition::Left,
                 &ActionIndex::PassUpcard,
             );
-            return None;
+            None
         }
         _ => panic!("invalid bid upcard action"),
     }
 }
 
-fn run_discard(
-    player: &NeuralNetwork,
+// The dealer picks up the upcard as the sixth card in hand and discards one
+pub fn run_discard(
+    player: &dyn Player,
     input: &mut NeuralNetworkInput,
     hand: &mut [Option<Card>; 6],
 ) {
@@ -391,11 +575,14 @@ fn run_discard(
     set_hand(input, hand);
 }
 
-fn run_bid_suit(
-    dealer_player: &NeuralNetwork,
-    position_1_player: &NeuralNetwork,
-    position_2_player: &NeuralNetwork,
-    position_3_player: &NeuralNetwork,
+// The second round of bidding on any suit but the upcard's, returns the team that called trump,
+// the suit, and which seat sits out of a loner
+#[allow(clippy::too_many_arguments)]
+pub fn run_bid_suit(
+    dealer_player: &dyn Player,
+    position_1_player: &dyn Player,
+    position_2_player: &dyn Player,
+    position_3_player: &dyn Player,
     dealer_input: &mut NeuralNetworkInput,
     position_1_input: &mut NeuralNetworkInput,
     position_2_input: &mut NeuralNetworkInput,
@@ -773,14 +960,14 @@ fn run_bid_suit(
 }
 
 fn get_bid_suit_action(
-    myself: &NeuralNetwork,
+    myself: &dyn Player,
     myself_input: &mut NeuralNetworkInput,
     left_input: &mut NeuralNetworkInput,
     ally_input: &mut NeuralNetworkInput,
     right_input: &mut NeuralNetworkInput,
     available_actions: &AvailableActions,
 ) -> Option<(bool, Suit, bool)> {
-    match myself.get_action(&myself_input, available_actions) {
+    match myself.get_action(myself_input, available_actions) {
         ActionIndex::MakeSuitSpade => {
             set_bid_suit(
                 myself_input,
@@ -802,7 +989,7 @@ fn get_bid_suit_action(
                 &RelativePosition::Left,
                 &ActionIndex::MakeSuitSpade,
             );
-            return Some((true, Suit::Spade, false));
+            Some((true, Suit::Spade, false))
         }
         ActionIndex::MakeSuitSpadeAlone => {
             set_bid_suit(
@@ -825,7 +1012,7 @@ fn get_bid_suit_action(
                 &RelativePosition::Left,
                 &ActionIndex::MakeSuitSpadeAlone,
             );
-            return Some((true, Suit::Spade, true));
+            Some((true, Suit::Spade, true))
         }
         ActionIndex::MakeSuitHeart => {
             set_bid_suit(
@@ -848,7 +1035,7 @@ fn get_bid_suit_action(
                 &RelativePosition::Left,
                 &ActionIndex::MakeSuitHeart,
             );
-            return Some((true, Suit::Heart, false));
+            Some((true, Suit::Heart, false))
         }
         ActionIndex::MakeSuitHeartAlone => {
             set_bid_suit(
@@ -871,7 +1058,7 @@ fn get_bid_suit_action(
                 &RelativePosition::Left,
                 &ActionIndex::MakeSuitHeartAlone,
             );
-            return Some((true, Suit::Heart, true));
+            Some((true, Suit::Heart, true))
         }
         ActionIndex::MakeSuitDiamond => {
             set_bid_suit(
@@ -894,7 +1081,7 @@ fn get_bid_suit_action(
                 &RelativePosition::Left,
                 &ActionIndex::MakeSuitDiamond,
             );
-            return Some((true, Suit::Diamond, false));
+            Some((true, Suit::Diamond, false))
         }
         ActionIndex::MakeSuitDiamondAlone => {
             set_bid_suit(
@@ -917,7 +1104,7 @@ fn get_bid_suit_action(
                 &RelativePosition::Left,
                 &ActionIndex::MakeSuitDiamondAlone,
             );
-            return Some((true, Suit::Diamond, true));
+            Some((true, Suit::Diamond, true))
         }
         ActionIndex::MakeSuitClub => {
             set_bid_suit(
@@ -940,7 +1127,7 @@ fn get_bid_suit_action(
                 &RelativePosition::Left,
                 &ActionIndex::MakeSuitClub,
             );
-            return Some((true, Suit::Club, false));
+            Some((true, Suit::Club, false))
         }
         ActionIndex::MakeSuitClubAlone => {
             set_bid_suit(
@@ -963,7 +1150,7 @@ fn get_bid_suit_action(
                 &RelativePosition::Left,
                 &ActionIndex::MakeSuitClubAlone,
             );
-            return Some((true, Suit::Club, true));
+            Some((true, Suit::Club, true))
         }
         ActionIndex::PassSuit => {
             set_bid_suit(
@@ -974,17 +1161,20 @@ fn get_bid_suit_action(
             set_bid_suit(left_input, &RelativePosition::Right, &ActionIndex::PassSuit);
             set_bid_suit(ally_input, &RelativePosition::Ally, &ActionIndex::PassSuit);
             set_bid_suit(right_input, &RelativePosition::Left, &ActionIndex::PassSuit);
-            return None;
+            None
         }
         _ => panic!("invalid bid upcard action"),
     }
 }
 
-fn run_tricks(
-    dealer_player: &NeuralNetwork,
-    position_1_player: &NeuralNetwork,
-    position_2_player: &NeuralNetwork,
-    position_3_player: &NeuralNetwork,
+// Plays out the five tricks once trump is set, returns the tricks taken by the dealer's team and
+// the other team
+#[allow(clippy::too_many_arguments)]
+pub fn run_tricks(
+    dealer_player: &dyn Player,
+    position_1_player: &dyn Player,
+    position_2_player: &dyn Player,
+    position_3_player: &dyn Player,
     dealer_input: &mut NeuralNetworkInput,
     position_1_input: &mut NeuralNetworkInput,
     position_2_input: &mut NeuralNetworkInput,
@@ -998,6 +1188,7 @@ fn run_tricks(
     skip_position_2: &bool,
     skip_position_3: &bool,
     trump_suit: &Suit,
+    summary: &mut RoundSummary,
 ) -> (u8, u8) {
     let (mut dealer_team_trick_count, mut other_team_trick_count): (u8, u8) = (0, 0);
     let mut lead = DealerRelativePosition::Left;
@@ -1047,12 +1238,13 @@ fn run_tricks(
                 position_1_hand,
                 position_2_hand,
                 position_3_hand,
-                &skip_dealer,
-                &skip_position_1,
-                &skip_position_2,
-                &skip_position_3,
+                skip_dealer,
+                skip_position_1,
+                skip_position_2,
+                skip_position_3,
                 trump_suit,
                 &trick_index,
+                summary,
             ),
             DealerRelativePosition::Left => run_trick(
                 &DealerRelativePosition::Left,
@@ -1068,12 +1260,13 @@ fn run_tricks(
                 position_2_hand,
                 position_3_hand,
                 dealer_hand,
-                &skip_position_1,
-                &skip_position_2,
-                &skip_position_3,
-                &skip_dealer,
+                skip_position_1,
+                skip_position_2,
+                skip_position_3,
+                skip_dealer,
                 trump_suit,
                 &trick_index,
+                summary,
             ),
             DealerRelativePosition::Ally => run_trick(
                 &DealerRelativePosition::Ally,
@@ -1089,12 +1282,13 @@ fn run_tricks(
                 position_3_hand,
                 dealer_hand,
                 position_1_hand,
-                &skip_position_2,
-                &skip_position_3,
-                &skip_dealer,
-                &skip_position_1,
+                skip_position_2,
+                skip_position_3,
+                skip_dealer,
+                skip_position_1,
                 trump_suit,
                 &trick_index,
+                summary,
             ),
             DealerRelativePosition::Right => run_trick(
                 &DealerRelativePosition::Right,
@@ -1110,12 +1304,13 @@ fn run_tricks(
                 dealer_hand,
                 position_1_hand,
                 position_2_hand,
-                &skip_position_3,
-                &skip_dealer,
-                &skip_position_1,
-                &skip_position_2,
+                skip_position_3,
+                skip_dealer,
+                skip_position_1,
+                skip_position_2,
                 trump_suit,
                 &trick_index,
+                summary,
             ),
         };
 
@@ -1155,12 +1350,13 @@ fn run_tricks(
     (dealer_team_trick_count, other_team_trick_count)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn run_trick(
     lead_position_relative_to_dealer: &DealerRelativePosition,
-    lead_player: &NeuralNetwork,
-    position_1_player: &NeuralNetwork,
-    position_2_player: &NeuralNetwork,
-    position_3_player: &NeuralNetwork,
+    lead_player: &dyn Player,
+    position_1_player: &dyn Player,
+    position_2_player: &dyn Player,
+    position_3_player: &dyn Player,
     lead_input: &mut NeuralNetworkInput,
     position_1_input: &mut NeuralNetworkInput,
     position_2_input: &mut NeuralNetworkInput,
@@ -1175,6 +1371,7 @@ fn run_trick(
     skip_position_3: &bool,
     trump_suit: &Suit,
     trick_index: &TrickIndex,
+    summary: &mut RoundSummary,
 ) -> DealerRelativePosition {
     if *trick_index != TrickIndex::Fifth {
         set_trick_lead(lead_input, &RelativePosition::Myself, trick_index);
@@ -1186,6 +1383,7 @@ fn run_trick(
     let mut winning_player_lead_relative_position: Option<LeadRelativePosition> = None;
     let mut lead_suit: Option<Suit> = None;
     let mut winning_card: Option<Card> = None;
+    let mut led: Option<(LeadRelativePosition, Card)> = None;
 
     get_trick_action(
         lead_player,
@@ -1194,7 +1392,7 @@ fn run_trick(
         position_2_input,
         position_3_input,
         lead_hand,
-        &skip_lead,
+        skip_lead,
         &mut winning_player_lead_relative_position,
         &LeadRelativePosition::Lead,
         &mut winning_card,
@@ -1202,6 +1400,7 @@ fn run_trick(
         trump_suit,
         trick_index,
         &TrickCardIndex::First,
+        &mut led,
     );
     get_trick_action(
         position_1_player,
@@ -1210,7 +1409,7 @@ fn run_trick(
         position_3_input,
         lead_input,
         position_1_hand,
-        &skip_position_1,
+        skip_position_1,
         &mut winning_player_lead_relative_position,
         &LeadRelativePosition::Left,
         &mut winning_card,
@@ -1218,6 +1417,7 @@ fn run_trick(
         trump_suit,
         trick_index,
         &TrickCardIndex::Second,
+        &mut led,
     );
     get_trick_action(
         position_2_player,
@@ -1226,7 +1426,7 @@ fn run_trick(
         lead_input,
         position_1_input,
         position_2_hand,
-        &skip_position_2,
+        skip_position_2,
         &mut winning_player_lead_relative_position,
         &LeadRelativePosition::Ally,
         &mut winning_card,
@@ -1234,6 +1434,7 @@ fn run_trick(
         trump_suit,
         trick_index,
         &TrickCardIndex::Third,
+        &mut led,
     );
     get_trick_action(
         position_3_player,
@@ -1242,7 +1443,7 @@ fn run_trick(
         position_1_input,
         position_2_input,
         position_3_hand,
-        &skip_position_3,
+        skip_position_3,
         &mut winning_player_lead_relative_position,
         &LeadRelativePosition::Right,
         &mut winning_card,
@@ -1250,8 +1451,21 @@ fn run_trick(
         trump_suit,
         trick_index,
         &TrickCardIndex::Fourth,
+        &mut led,
     );
 
+    if *trick_index != TrickIndex::Fifth {
+        if let Some((lead_relative_position, card)) = led {
+            summary.leads.push((
+                (summary.dealer
+                    + *lead_position_relative_to_dealer as usize
+                    + lead_relative_position as usize)
+                    % 4,
+                is_trump(&card, trump_suit),
+            ));
+        }
+    }
+
     match (
         winning_player_lead_relative_position,
         lead_position_relative_to_dealer,
@@ -1260,32 +1474,33 @@ fn run_trick(
         | (Some(LeadRelativePosition::Left), DealerRelativePosition::Right)
         | (Some(LeadRelativePosition::Ally), DealerRelativePosition::Ally)
         | (Some(LeadRelativePosition::Right), DealerRelativePosition::Left) => {
-            return DealerRelativePosition::Dealer
+            DealerRelativePosition::Dealer
         }
         (Some(LeadRelativePosition::Lead), DealerRelativePosition::Left)
         | (Some(LeadRelativePosition::Left), DealerRelativePosition::Dealer)
         | (Some(LeadRelativePosition::Ally), DealerRelativePosition::Right)
         | (Some(LeadRelativePosition::Right), DealerRelativePosition::Ally) => {
-            return DealerRelativePosition::Left
+            DealerRelativePosition::Left
         }
         (Some(LeadRelativePosition::Lead), DealerRelativePosition::Ally)
         | (Some(LeadRelativePosition::Left), DealerRelativePosition::Left)
         | (Some(LeadRelativePosition::Ally), DealerRelativePosition::Dealer)
         | (Some(LeadRelativePosition::Right), DealerRelativePosition::Right) => {
-            return DealerRelativePosition::Ally
+            DealerRelativePosition::Ally
         }
         (Some(LeadRelativePosition::Lead), DealerRelativePosition::Right)
         | (Some(LeadRelativePosition::Left), DealerRelativePosition::Ally)
         | (Some(LeadRelativePosition::Ally), DealerRelativePosition::Left)
         | (Some(LeadRelativePosition::Right), DealerRelativePosition::Dealer) => {
-            return DealerRelativePosition::Right
+            DealerRelativePosition::Right
         }
         _ => panic!("impossible lead/dealer relative position pairing"),
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn get_trick_action(
-    player: &NeuralNetwork,
+    player: &dyn Player,
     input: &mut NeuralNetworkInput,
     other_input_1: &mut NeuralNetworkInput,
     other_input_2: &mut NeuralNetworkInput,
@@ -1299,6 +1514,7 @@ fn get_trick_action(
     trump_suit: &Suit,
     trick_index: &TrickIndex,
     trick_card_index: &TrickCardIndex,
+    led: &mut Option<(LeadRelativePosition, Card)>,
 ) {
     if *skip {
         return;
@@ -1312,22 +1528,23 @@ fn get_trick_action(
             }
         }
     } else {
-        let available_actions = get_play_available_actions(hand, &lead_suit);
+        let available_actions = get_play_available_actions(hand, lead_suit);
         action = Some(player.get_action(input, &available_actions));
     }
     let action = action.unwrap();
     let card = play_from_hand(hand, &action);
-    if card_wins(&card, &winning_card, &lead_suit, &trump_suit) {
+    if card_wins(&card, winning_card, lead_suit, trump_suit) {
         *winning_player_lead_relative_position = Some(*lead_relative_position);
         *winning_card = Some(card);
     }
     if lead_suit.is_none() {
+        *led = Some((*lead_relative_position, card));
         *lead_suit = Some(card.suit);
         if *trick_index != TrickIndex::Fifth {
-            set_trick_lead_suit(input, &lead_suit, trick_index);
-            set_trick_lead_suit(other_input_1, &lead_suit, trick_index);
-            set_trick_lead_suit(other_input_2, &lead_suit, trick_index);
-            set_trick_lead_suit(other_input_3, &lead_suit, trick_index);
+            set_trick_lead_suit(input, lead_suit, trick_index);
+            set_trick_lead_suit(other_input_1, lead_suit, trick_index);
+            set_trick_lead_suit(other_input_2, lead_suit, trick_index);
+            set_trick_lead_suit(other_input_3, lead_suit, trick_index);
         }
     }
     if *trick_index != TrickIndex::Fifth {
@@ -1337,3 +1554,99 @@ fn get_trick_action(
         set_trick_card_played(other_input_3, &card, trick_index, trick_card_index);
     }
 }
+
+fn seat_index(position: &Position) -> usize {
+    match position {
+        Position::North => 0,
+        Position::East => 1,
+        Position::South => 2,
+        Position::West => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEALER: Option<RelativeTeam> = Some(RelativeTeam::Dealer);
+    const OTHER: Option<RelativeTeam> = Some(RelativeTeam::Other);
+
+    fn summary() -> RoundSummary {
+        RoundSummary {
+            dealer: 3,
+            maker: Some(1),
+            ordered_up: true,
+            alone: false,
+            tricks: [2, 3],
+            points: [0, 1],
+            leads: vec![(0, false), (1, true), (3, false), (1, true)],
+        }
+    }
+
+    #[test]
+    fn rotated_moves_seats_left_and_swaps_teams_on_odd_offsets() {
+        let rotated = summary().rotated(1);
+        assert_eq!(rotated.dealer, 0);
+        assert_eq!(rotated.maker, Some(2));
+        assert_eq!(rotated.tricks, [3, 2]);
+        assert_eq!(rotated.points, [1, 0]);
+        assert_eq!(
+            rotated.leads,
+            vec![(1, false), (2, true), (0, false), (2, true)]
+        );
+        assert!(rotated.ordered_up && !rotated.alone);
+    }
+
+    #[test]
+    fn rotated_keeps_teams_on_even_offsets() {
+        let rotated = summary().rotated(2);
+        assert_eq!((rotated.dealer, rotated.maker), (1, Some(3)));
+        assert_eq!((rotated.tricks, rotated.points), ([2, 3], [0, 1]));
+        let full_turn = summary().rotated(4);
+        assert_eq!(
+            (full_turn.dealer, full_turn.maker, full_turn.leads),
+            (3, Some(1), summary().leads)
+        );
+    }
+
+    #[test]
+    fn score_round_gives_makers_one_point_for_three_or_four_tricks() {
+        assert_eq!(
+            score_round(DEALER, 3, 2, false, false, false, false),
+            (1, 0)
+        );
+        assert_eq!(
+            score_round(DEALER, 4, 1, false, false, false, false),
+            (1, 0)
+        );
+        assert_eq!(score_round(OTHER, 1, 4, false, false, false, false), (0, 1));
+    }
+
+    #[test]
+    fn score_round_gives_two_points_for_a_march_or_a_euchre() {
+        assert_eq!(
+            score_round(DEALER, 5, 0, false, false, false, false),
+            (2, 0)
+        );
+        assert_eq!(score_round(OTHER, 0, 5, false, false, false, false), (0, 2));
+        // euchred makers give the defenders two
+        assert_eq!(
+            score_round(DEALER, 2, 3, false, false, false, false),
+            (0, 2)
+        );
+        assert_eq!(score_round(OTHER, 5, 0, false, false, false, false), (2, 0));
+    }
+
+    #[test]
+    fn score_round_gives_four_points_for_a_loner_march() {
+        // the skipped seat is the loner's partner
+        assert_eq!(score_round(DEALER, 5, 0, true, false, false, false), (4, 0));
+        assert_eq!(score_round(DEALER, 5, 0, false, false, true, false), (4, 0));
+        assert_eq!(score_round(OTHER, 0, 5, false, true, false, false), (0, 4));
+        assert_eq!(score_round(OTHER, 0, 5, false, false, false, true), (0, 4));
+        // a loner that only takes three still scores one
+        assert_eq!(score_round(DEALER, 3, 2, true, false, false, false), (1, 0));
+        // and a euchred loner gives up two
+        assert_eq!(score_round(OTHER, 3, 2, false, true, false, false), (2, 0));
+    }
+}
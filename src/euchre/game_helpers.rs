@@ -1,5 +1,5 @@
 use super::{constants::*, enums::*, types::*};
-use rand::seq::SliceRandom;
+use rand::{seq::SliceRandom, Rng};
 use strum::EnumCount;
 
 fn adjust_card_suit(card: &Card, trump_suit: &Suit) -> Suit {
@@ -7,11 +7,15 @@ fn adjust_card_suit(card: &Card, trump_suit: &Suit) -> Suit {
         (Rank::Jack, Suit::Spade, Suit::Club)
         | (Rank::Jack, Suit::Club, Suit::Spade)
         | (Rank::Jack, Suit::Diamond, Suit::Heart)
-        | (Rank::Jack, Suit::Heart, Suit::Diamond) => return *trump_suit,
-        _ => return card.suit,
+        | (Rank::Jack, Suit::Heart, Suit::Diamond) => *trump_suit,
+        _ => card.suit,
     }
 }
 
+pub fn is_trump(card: &Card, trump_suit: &Suit) -> bool {
+    adjust_card_suit(card, trump_suit) == *trump_suit
+}
+
 pub fn card_wins(
     card: &Card,
     winning_card: &Option<Card>,
@@ -67,7 +71,7 @@ pub fn card_wins(
             }
             panic!("invalid winning card")
         }
-        _ => return true,
+        _ => true,
     }
 }
 
@@ -103,9 +107,9 @@ pub fn play_from_hand(hand: &mut [Option<Card>; 6], action: &ActionIndex) -> Car
         ActionIndex::PlayClubAce => CARD_CLUB_ACE,
         _ => panic!("invalid play action attempted"),
     };
-    for i in 0..hand.len() {
-        if hand[i].is_some() && hand[i].unwrap() == selected_card {
-            hand[i] = None;
+    for slot in hand.iter_mut() {
+        if *slot == Some(selected_card) {
+            *slot = None;
             return selected_card;
         }
     }
@@ -144,9 +148,9 @@ pub fn discard_from_hand(hand: &mut [Option<Card>; 6], action: &ActionIndex) {
         ActionIndex::DiscardClubAce => CARD_CLUB_ACE,
         _ => panic!("invalid discard action attempted"),
     };
-    for i in 0..hand.len() {
-        if hand[i].is_some() && hand[i].unwrap() == selected_card {
-            hand[i] = None;
+    for slot in hand.iter_mut() {
+        if *slot == Some(selected_card) {
+            *slot = None;
             return;
         }
     }
@@ -155,23 +159,20 @@ pub fn discard_from_hand(hand: &mut [Option<Card>; 6], action: &ActionIndex) {
 
 pub fn left_player(player: &Position) -> &Position {
     match player {
-        Position::North => return &Position::East,
-        Position::East => return &Position::South,
-        Position::South => return &Position::West,
-        Position::West => return &Position::North,
+        Position::North => &Position::East,
+        Position::East => &Position::South,
+        Position::South => &Position::West,
+        Position::West => &Position::North,
     }
 }
 
-pub fn deal() -> (
-    [Option<Card>; 6],
-    [Option<Card>; 6],
-    [Option<Card>; 6],
-    [Option<Card>; 6],
-    Card,
-) {
-    let mut deck: [Card; Rank::COUNT * Suit::COUNT] = DECK.clone();
-    deck.shuffle(&mut rand::thread_rng());
-    return (
+pub type Hand = [Option<Card>; 6];
+
+// dealer, then the three players to the dealer's left, then the upcard
+pub fn deal<R: Rng>(rng: &mut R) -> (Hand, Hand, Hand, Hand, Card) {
+    let mut deck: [Card; Rank::COUNT * Suit::COUNT] = DECK;
+    deck.shuffle(rng);
+    (
         [
             Some(deck[0]),
             Some(deck[1]),
@@ -205,5 +206,5 @@ pub fn deal() -> (
             None,
         ],
         deck[20],
-    );
+    )
 }
@@ -1,6 +1,10 @@
+pub mod bots;
 mod constants;
 pub mod enums;
 pub mod game;
 mod game_helpers;
 mod neural_network_helpers;
+pub mod player;
+pub mod puzzles;
+pub mod statistics;
 mod types;
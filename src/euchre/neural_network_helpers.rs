@@ -19,122 +19,84 @@ pub fn get_play_available_actions(
 
     let mut must_follow = false;
 
-    match lead_suit {
-        Some(lead_suit) => {
-            for card in hand {
-                if card.is_some() && card.unwrap().suit == *lead_suit {
-                    must_follow = true;
-                    break;
-                }
+    if let Some(lead_suit) = lead_suit {
+        for card in hand {
+            if card.is_some() && card.unwrap().suit == *lead_suit {
+                must_follow = true;
+                break;
             }
         }
-        _ => {}
     }
 
-    for card in hand {
-        match card {
-            Some(card) => {
-                if must_follow && card.suit != lead_suit.unwrap() {
-                    continue;
-                }
-                match *card {
-                    // Spade
-                    CARD_SPADE_NINE => {
-                        available_actions[ActionIndex::PlaySpadeNine as usize] = true
-                    }
-                    CARD_SPADE_TEN => available_actions[ActionIndex::PlaySpadeTen as usize] = true,
-                    CARD_SPADE_JACK => {
-                        available_actions[ActionIndex::PlaySpadeJack as usize] = true
-                    }
-                    CARD_SPADE_QUEEN => {
-                        available_actions[ActionIndex::PlaySpadeQueen as usize] = true
-                    }
-                    CARD_SPADE_KING => {
-                        available_actions[ActionIndex::PlaySpadeKing as usize] = true
-                    }
-                    CARD_SPADE_ACE => available_actions[ActionIndex::PlaySpadeAce as usize] = true,
-                    // Heart
-                    CARD_HEART_NINE => {
-                        available_actions[ActionIndex::PlayHeartNine as usize] = true
-                    }
-                    CARD_HEART_TEN => available_actions[ActionIndex::PlayHeartTen as usize] = true,
-                    CARD_HEART_JACK => {
-                        available_actions[ActionIndex::PlayHeartJack as usize] = true
-                    }
-                    CARD_HEART_QUEEN => {
-                        available_actions[ActionIndex::PlayHeartQueen as usize] = true
-                    }
-                    CARD_HEART_KING => {
-                        available_actions[ActionIndex::PlayHeartKing as usize] = true
-                    }
-                    CARD_HEART_ACE => available_actions[ActionIndex::PlayHeartAce as usize] = true,
-                    // Diamond
-                    CARD_DIAMOND_NINE => {
-                        available_actions[ActionIndex::PlayDiamondNine as usize] = true
-                    }
-                    CARD_DIAMOND_TEN => {
-                        available_actions[ActionIndex::PlayDiamondTen as usize] = true
-                    }
-                    CARD_DIAMOND_JACK => {
-                        available_actions[ActionIndex::PlayDiamondJack as usize] = true
-                    }
-                    CARD_DIAMOND_QUEEN => {
-                        available_actions[ActionIndex::PlayDiamondQueen as usize] = true
-                    }
-                    CARD_DIAMOND_KING => {
-                        available_actions[ActionIndex::PlayDiamondKing as usize] = true
-                    }
-                    CARD_DIAMOND_ACE => {
-                        available_actions[ActionIndex::PlayDiamondAce as usize] = true
-                    }
-                    // Club
-                    CARD_CLUB_NINE => available_actions[ActionIndex::PlayClubNine as usize] = true,
-                    CARD_CLUB_TEN => available_actions[ActionIndex::PlayClubTen as usize] = true,
-                    CARD_CLUB_JACK => available_actions[ActionIndex::PlayClubJack as usize] = true,
-                    CARD_CLUB_QUEEN => {
-                        available_actions[ActionIndex::PlayClubQueen as usize] = true
-                    }
-                    CARD_CLUB_KING => available_actions[ActionIndex::PlayClubKing as usize] = true,
-                    CARD_CLUB_ACE => available_actions[ActionIndex::PlayClubAce as usize] = true,
-                }
-            }
-            _ => {}
+    for card in hand.iter().flatten() {
+        if must_follow && card.suit != lead_suit.unwrap() {
+            continue;
+        }
+        match *card {
+            // Spade
+            CARD_SPADE_NINE => available_actions[ActionIndex::PlaySpadeNine as usize] = true,
+            CARD_SPADE_TEN => available_actions[ActionIndex::PlaySpadeTen as usize] = true,
+            CARD_SPADE_JACK => available_actions[ActionIndex::PlaySpadeJack as usize] = true,
+            CARD_SPADE_QUEEN => available_actions[ActionIndex::PlaySpadeQueen as usize] = true,
+            CARD_SPADE_KING => available_actions[ActionIndex::PlaySpadeKing as usize] = true,
+            CARD_SPADE_ACE => available_actions[ActionIndex::PlaySpadeAce as usize] = true,
+            // Heart
+            CARD_HEART_NINE => available_actions[ActionIndex::PlayHeartNine as usize] = true,
+            CARD_HEART_TEN => available_actions[ActionIndex::PlayHeartTen as usize] = true,
+            CARD_HEART_JACK => available_actions[ActionIndex::PlayHeartJack as usize] = true,
+            CARD_HEART_QUEEN => available_actions[ActionIndex::PlayHeartQueen as usize] = true,
+            CARD_HEART_KING => available_actions[ActionIndex::PlayHeartKing as usize] = true,
+            CARD_HEART_ACE => available_actions[ActionIndex::PlayHeartAce as usize] = true,
+            // Diamond
+            CARD_DIAMOND_NINE => available_actions[ActionIndex::PlayDiamondNine as usize] = true,
+            CARD_DIAMOND_TEN => available_actions[ActionIndex::PlayDiamondTen as usize] = true,
+            CARD_DIAMOND_JACK => available_actions[ActionIndex::PlayDiamondJack as usize] = true,
+            CARD_DIAMOND_QUEEN => available_actions[ActionIndex::PlayDiamondQueen as usize] = true,
+            CARD_DIAMOND_KING => available_actions[ActionIndex::PlayDiamondKing as usize] = true,
+            CARD_DIAMOND_ACE => available_actions[ActionIndex::PlayDiamondAce as usize] = true,
+            // Club
+            CARD_CLUB_NINE => available_actions[ActionIndex::PlayClubNine as usize] = true,
+            CARD_CLUB_TEN => available_actions[ActionIndex::PlayClubTen as usize] = true,
+            CARD_CLUB_JACK => available_actions[ActionIndex::PlayClubJack as usize] = true,
+            CARD_CLUB_QUEEN => available_actions[ActionIndex::PlayClubQueen as usize] = true,
+            CARD_CLUB_KING => available_actions[ActionIndex::PlayClubKing as usize] = true,
+            CARD_CLUB_ACE => available_actions[ActionIndex::PlayClubAce as usize] = true,
         }
     }
 
-    return available_actions;
+    available_actions
 }
 
 pub fn get_card_play_action(card: &Card) -> ActionIndex {
     match *card {
         // Spade
-        CARD_SPADE_NINE => return ActionIndex::PlaySpadeNine,
-        CARD_SPADE_TEN => return ActionIndex::PlaySpadeTen,
-        CARD_SPADE_JACK => return ActionIndex::PlaySpadeJack,
-        CARD_SPADE_QUEEN => return ActionIndex::PlaySpadeQueen,
-        CARD_SPADE_KING => return ActionIndex::PlaySpadeKing,
-        CARD_SPADE_ACE => return ActionIndex::PlaySpadeAce,
+        CARD_SPADE_NINE => ActionIndex::PlaySpadeNine,
+        CARD_SPADE_TEN => ActionIndex::PlaySpadeTen,
+        CARD_SPADE_JACK => ActionIndex::PlaySpadeJack,
+        CARD_SPADE_QUEEN => ActionIndex::PlaySpadeQueen,
+        CARD_SPADE_KING => ActionIndex::PlaySpadeKing,
+        CARD_SPADE_ACE => ActionIndex::PlaySpadeAce,
         // Heart
-        CARD_HEART_NINE => return ActionIndex::PlayHeartNine,
-        CARD_HEART_TEN => return ActionIndex::PlayHeartTen,
-        CARD_HEART_JACK => return ActionIndex::PlayHeartJack,
-        CARD_HEART_QUEEN => return ActionIndex::PlayHeartQueen,
-        CARD_HEART_KING => return ActionIndex::PlayHeartKing,
-        CARD_HEART_ACE => return ActionIndex::PlayHeartAce,
+        CARD_HEART_NINE => ActionIndex::PlayHeartNine,
+        CARD_HEART_TEN => ActionIndex::PlayHeartTen,
+        CARD_HEART_JACK => ActionIndex::PlayHeartJack,
+        CARD_HEART_QUEEN => ActionIndex::PlayHeartQueen,
+        CARD_HEART_KING => ActionIndex::PlayHeartKing,
+        CARD_HEART_ACE => ActionIndex::PlayHeartAce,
         // Diamond
-        CARD_DIAMOND_NINE => return ActionIndex::PlayDiamondNine,
-        CARD_DIAMOND_TEN => return ActionIndex::PlayDiamondTen,
-        CARD_DIAMOND_JACK => return ActionIndex::PlayDiamondJack,
-        CARD_DIAMOND_QUEEN => return ActionIndex::PlayDiamondQueen,
-        CARD_DIAMOND_KING => return ActionIndex::PlayDiamondKing,
-        CARD_DIAMOND_ACE => return ActionIndex::PlayDiamondAce,
+        CARD_DIAMOND_NINE => ActionIndex::PlayDiamondNine,
+        CARD_DIAMOND_TEN => ActionIndex::PlayDiamondTen,
+        CARD_DIAMOND_JACK => ActionIndex::PlayDiamondJack,
+        CARD_DIAMOND_QUEEN => ActionIndex::PlayDiamondQueen,
+        CARD_DIAMOND_KING => ActionIndex::PlayDiamondKing,
+        CARD_DIAMOND_ACE => ActionIndex::PlayDiamondAce,
         // Club
-        CARD_CLUB_NINE => return ActionIndex::PlayClubNine,
-        CARD_CLUB_TEN => return ActionIndex::PlayClubTen,
-        CARD_CLUB_JACK => return ActionIndex::PlayClubJack,
-        CARD_CLUB_QUEEN => return ActionIndex::PlayClubQueen,
-        CARD_CLUB_KING => return ActionIndex::PlayClubKing,
-        CARD_CLUB_ACE => return ActionIndex::PlayClubAce,
+        CARD_CLUB_NINE => ActionIndex::PlayClubNine,
+        CARD_CLUB_TEN => ActionIndex::PlayClubTen,
+        CARD_CLUB_JACK => ActionIndex::PlayClubJack,
+        CARD_CLUB_QUEEN => ActionIndex::PlayClubQueen,
+        CARD_CLUB_KING => ActionIndex::PlayClubKing,
+        CARD_CLUB_ACE => ActionIndex::PlayClubAce,
     }
 }
 
@@ -201,8 +163,8 @@ pub fn set_trick_lead_suit(
     lead_suit: &Option<Suit>,
     trick_index: &TrickIndex,
 ) {
-    match lead_suit {
-        Some(lead_suit) => match (lead_suit, trick_index) {
+    if let Some(lead_suit) = lead_suit {
+        match (lead_suit, trick_index) {
             (Suit::Spade, TrickIndex::First) => {
                 input[StateIndex::Trick1LeadSuitSpade as usize] = 1.0
             }
@@ -248,8 +210,7 @@ pub fn set_trick_lead_suit(
                 input[StateIndex::Trick4LeadSuitClub as usize] = 1.0
             }
             _ => panic!("invalid lead suit and trick index combination"),
-        },
-        _ => return,
+        }
     }
 }
 
@@ -1843,3 +1804,20 @@ pub fn set_score(input: &mut NeuralNetworkInput, ally_score: &u8, enemy_score: &
         _ => panic!("invalid enemy score"),
     }
 }
+
+// Position relative to the input's seat (Myself, Left, Ally, Right from 0) that named trump, and
+// whether it ordered up the upcard rather than calling a suit
+pub fn get_maker(input: &NeuralNetworkInput) -> Option<(usize, bool)> {
+    for position in 0..4 {
+        let upcard = StateIndex::BidUpcardMyselfMake as usize + position * 3;
+        if input[upcard] != 0.0 || input[upcard + 1] != 0.0 {
+            return Some((position, true));
+        }
+        // four slots to make each suit, four to make each suit alone, then pass
+        let suit = StateIndex::BidSuitMyselfMakeSpade as usize + position * 9;
+        if input[suit..suit + 8].iter().any(|&x| x != 0.0) {
+            return Some((position, false));
+        }
+    }
+    None
+}
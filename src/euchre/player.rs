@@ -0,0 +1,29 @@
+use crate::organism::neural_network::{AvailableActions, NeuralNetworkInput};
+
+use super::enums::ActionIndex;
+
+// Anything that can take a seat at the table
+pub trait Player {
+    fn get_action(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex;
+
+    // Points scored by this player's team and the other team in the round just played
+    fn end_round(&self, _own_score_delta: u8, _other_score_delta: u8) {}
+}
+
+impl<P: Player + ?Sized> Player for &P {
+    fn get_action(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex {
+        (**self).get_action(inputs, available_actions)
+    }
+
+    fn end_round(&self, own_score_delta: u8, other_score_delta: u8) {
+        (**self).end_round(own_score_delta, other_score_delta)
+    }
+}
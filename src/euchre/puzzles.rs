@@ -0,0 +1,550 @@
+use std::{collections::VecDeque, error::Error, fs, path::Path, sync::Mutex};
+
+use serde::{Deserialize, Serialize};
+use strum::EnumCount;
+
+use crate::organism::neural_network::{AvailableActions, NeuralNetworkInput};
+
+use super::{
+    constants::DECK,
+    enums::{ActionIndex, Rank, Suit},
+    game::play_dealt_round,
+    player::Player,
+    statistics::SEAT_NAMES,
+    types::Card,
+};
+
+const SUIT_NAMES: [&str; Suit::COUNT] = ["spades", "hearts", "diamonds", "clubs"];
+const RANK_NAMES: [&str; Rank::COUNT] = ["9", "10", "J", "Q", "K", "A"];
+const SUIT_LETTERS: [&str; Suit::COUNT] = ["S", "H", "D", "C"];
+
+const PLAY_OFFSET: usize = ActionIndex::PlaySpadeNine as usize;
+const DISCARD_OFFSET: usize = ActionIndex::DiscardSpadeNine as usize;
+const MAKE_SUIT_OFFSET: usize = ActionIndex::MakeSuitSpade as usize;
+const MAKE_SUIT_ALONE_OFFSET: usize = ActionIndex::MakeSuitSpadeAlone as usize;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PuzzleSuite {
+    pub puzzles: Vec<Puzzle>,
+}
+
+// A point in a round and the answers that solve it. Cards are written rank then suit, such as
+// "JH" or "10s", and actions as "order", "order_alone", "pass", "call:<suit>",
+// "call_alone:<suit>", "discard:<card>" or "play:<card>"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Puzzle {
+    pub name: String,
+    pub dealer: String,
+    // seat asked what it would do, the actions so far have to end on its turn
+    pub seat: String,
+    // north/south and east/west before the round
+    #[serde(default)]
+    pub score: [u8; 2],
+    pub hands: Hands,
+    pub upcard: String,
+    // everything done in the round so far, in the order the seats did it
+    #[serde(default)]
+    pub actions: Vec<String>,
+    // the answer has to be one of these, any answer that isn't rejected will do when empty
+    #[serde(default)]
+    pub accept: Vec<String>,
+    #[serde(default)]
+    pub reject: Vec<String>,
+}
+
+// Five cards for each seat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hands {
+    pub north: Vec<String>,
+    pub east: Vec<String>,
+    pub south: Vec<String>,
+    pub west: Vec<String>,
+}
+
+pub struct PuzzleResult {
+    pub answer: String,
+    pub solved: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Move {
+    Order,
+    OrderAlone,
+    // in either round of bidding
+    Pass,
+    Call(usize),
+    CallAlone(usize),
+    Discard(usize),
+    Play(usize),
+}
+
+// A puzzle with its seats and cards read, seats are indexed North, East, South, West
+struct Situation {
+    dealer: usize,
+    seat: usize,
+    hands: [[Option<Card>; 6]; 4],
+    upcard: Card,
+    actions: Vec<Move>,
+    accept: Vec<Move>,
+    reject: Vec<Move>,
+}
+
+// Seats are written in lower case in puzzle files
+fn seat_name(seat: usize) -> String {
+    SEAT_NAMES[seat].to_lowercase()
+}
+
+fn parse_seat(s: &str) -> Result<usize, String> {
+    SEAT_NAMES
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(s))
+        .ok_or(format!("unknown seat {}", s))
+}
+
+fn parse_suit(s: &str) -> Result<usize, String> {
+    let lowered = s.to_lowercase();
+    (0..Suit::COUNT)
+        .find(|&i| lowered == SUIT_NAMES[i] || lowered == SUIT_LETTERS[i].to_lowercase())
+        .ok_or(format!("unknown suit {}", s))
+}
+
+// Index of the card in the deck, suits in order and nine up to ace within each
+fn parse_card(s: &str) -> Result<usize, String> {
+    let upper = s.to_uppercase();
+    let (rank, suit) = match upper.char_indices().last() {
+        Some((i, _)) => upper.split_at(i),
+        None => return Err("empty card".to_string()),
+    };
+    let rank = match rank {
+        "T" => 1,
+        _ => RANK_NAMES
+            .iter()
+            .position(|&name| name == rank)
+            .ok_or(format!("unknown card {}", s))?,
+    };
+    let suit = SUIT_LETTERS
+        .iter()
+        .position(|&letter| letter == suit)
+        .ok_or(format!("unknown card {}", s))?;
+    Ok(suit * Rank::COUNT + rank)
+}
+
+fn card_name(card: usize) -> String {
+    format!(
+        "{}{}",
+        RANK_NAMES[card % Rank::COUNT],
+        SUIT_LETTERS[card / Rank::COUNT]
+    )
+}
+
+impl Move {
+    fn parse(s: &str) -> Result<Move, String> {
+        let lowered = s.to_lowercase();
+        let (name, parameter) = match lowered.split_once(':') {
+            Some((name, parameter)) => (name, Some(parameter)),
+            None => (lowered.as_str(), None),
+        };
+        match (name, parameter) {
+            ("order", None) => Ok(Move::Order),
+            ("order_alone", None) => Ok(Move::OrderAlone),
+            ("pass", None) => Ok(Move::Pass),
+            ("call", Some(suit)) => Ok(Move::Call(parse_suit(suit)?)),
+            ("call_alone", Some(suit)) => Ok(Move::CallAlone(parse_suit(suit)?)),
+            ("discard", Some(card)) => Ok(Move::Discard(parse_card(card)?)),
+            ("play", Some(card)) => Ok(Move::Play(parse_card(card)?)),
+            _ => Err(format!("unknown action {}", s)),
+        }
+    }
+
+    fn from_action(action: ActionIndex) -> Move {
+        let index = action as usize;
+        match action {
+            ActionIndex::MakeUpcard => Move::Order,
+            ActionIndex::MakeUpcardAlone => Move::OrderAlone,
+            ActionIndex::PassUpcard | ActionIndex::PassSuit => Move::Pass,
+            _ if index >= MAKE_SUIT_ALONE_OFFSET => Move::CallAlone(index - MAKE_SUIT_ALONE_OFFSET),
+            _ if index >= MAKE_SUIT_OFFSET => Move::Call(index - MAKE_SUIT_OFFSET),
+            _ if index >= DISCARD_OFFSET => Move::Discard(index - DISCARD_OFFSET),
+            _ => Move::Play(index - PLAY_OFFSET),
+        }
+    }
+
+    // The actions this move stands for, passing is one action in each round of bidding
+    fn actions(&self) -> Vec<ActionIndex> {
+        match *self {
+            Move::Order => vec![ActionIndex::MakeUpcard],
+            Move::OrderAlone => vec![ActionIndex::MakeUpcardAlone],
+            Move::Pass => vec![ActionIndex::PassUpcard, ActionIndex::PassSuit],
+            Move::Call(suit) => vec![ActionIndex::from_usize(MAKE_SUIT_OFFSET + suit)],
+            Move::CallAlone(suit) => vec![ActionIndex::from_usize(MAKE_SUIT_ALONE_OFFSET + suit)],
+            Move::Discard(card) => vec![ActionIndex::from_usize(DISCARD_OFFSET + card)],
+            Move::Play(card) => vec![ActionIndex::from_usize(PLAY_OFFSET + card)],
+        }
+    }
+
+    fn name(&self) -> String {
+        match *self {
+            Move::Order => "order".to_string(),
+            Move::OrderAlone => "order_alone".to_string(),
+            Move::Pass => "pass".to_string(),
+            Move::Call(suit) => format!("call:{}", SUIT_NAMES[suit]),
+            Move::CallAlone(suit) => format!("call_alone:{}", SUIT_NAMES[suit]),
+            Move::Discard(card) => format!("discard:{}", card_name(card)),
+            Move::Play(card) => format!("play:{}", card_name(card)),
+        }
+    }
+}
+
+fn parse_moves(moves: &[String]) -> Result<Vec<Move>, String> {
+    moves.iter().map(|s| Move::parse(s)).collect()
+}
+
+impl PuzzleSuite {
+    pub fn load_from_file(filename: &str) -> Result<PuzzleSuite, Box<dyn Error>> {
+        let contents = fs::read_to_string(filename)?;
+        let suite = match Path::new(filename).extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&contents)?,
+            _ => serde_json::from_str(&contents)?,
+        };
+        Ok(suite)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.puzzles.is_empty() {
+            return Err("the suite has no puzzles".to_string());
+        }
+        for puzzle in self.puzzles.iter() {
+            puzzle
+                .situation()
+                .map_err(|e| format!("puzzle {}: {}", puzzle.name, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Puzzle {
+    fn situation(&self) -> Result<Situation, String> {
+        let mut seen = vec![false; DECK.len()];
+        let mut deal = |card: &str| -> Result<Card, String> {
+            let index = parse_card(card)?;
+            if seen[index] {
+                return Err(format!("{} is dealt twice", card_name(index)));
+            }
+            seen[index] = true;
+            Ok(DECK[index])
+        };
+        let mut hands = [[None; 6]; 4];
+        for (seat, cards) in [
+            &self.hands.north,
+            &self.hands.east,
+            &self.hands.south,
+            &self.hands.west,
+        ]
+        .iter()
+        .enumerate()
+        {
+            if cards.len() != 5 {
+                return Err(format!("{} needs 5 cards", seat_name(seat)));
+            }
+            for (slot, card) in cards.iter().enumerate() {
+                hands[seat][slot] = Some(deal(card)?);
+            }
+        }
+        let upcard = deal(&self.upcard)?;
+        if self.score.iter().any(|&score| score > 9) {
+            return Err("score must be within [0, 9]".to_string());
+        }
+        let situation = Situation {
+            dealer: parse_seat(&self.dealer)?,
+            seat: parse_seat(&self.seat)?,
+            hands,
+            upcard,
+            actions: parse_moves(&self.actions)?,
+            accept: parse_moves(&self.accept)?,
+            reject: parse_moves(&self.reject)?,
+        };
+        if situation.accept.is_empty() && situation.reject.is_empty() {
+            return Err("needs answers to accept or reject".to_string());
+        }
+        Ok(situation)
+    }
+
+    // Replays the actions through the engine with agent sitting in the puzzle's seat, so a
+    // recurrent network sees every decision it would have made, then asks it for the next one
+    pub fn solve(&self, agent: &dyn Player) -> Result<PuzzleResult, String> {
+        let situation = self.situation()?;
+        let replay = Mutex::new(Replay {
+            moves: situation.actions.iter().copied().collect(),
+            answer: None,
+            error: None,
+        });
+        // seats counted from the dealer, as the engine plays them
+        let order: [usize; 4] = [0, 1, 2, 3].map(|i| (situation.dealer + i) % 4);
+        let seats = order.map(|seat| PuzzleSeat {
+            seat,
+            puzzle_seat: situation.seat,
+            agent,
+            replay: &replay,
+        });
+        let players = [0, 1, 2, 3].map(|i| &seats[i] as &dyn Player);
+        play_dealt_round(
+            players,
+            players,
+            order.map(|seat| situation.hands[seat]),
+            situation.upcard,
+            [
+                self.score[situation.dealer % 2],
+                self.score[(situation.dealer + 1) % 2],
+            ],
+        );
+        let replay = replay.into_inner().unwrap();
+        if let Some(error) = replay.error {
+            return Err(error);
+        }
+        let answer = match replay.answer {
+            Some(answer) => Move::from_action(answer),
+            None => {
+                return Err(format!(
+                    "the round ended before {} was asked",
+                    seat_name(situation.seat)
+                ))
+            }
+        };
+        let solved = (situation.accept.is_empty() || situation.accept.contains(&answer))
+            && !situation.reject.contains(&answer);
+        Ok(PuzzleResult {
+            answer: answer.name(),
+            solved,
+        })
+    }
+}
+
+struct Replay {
+    moves: VecDeque<Move>,
+    answer: Option<ActionIndex>,
+    // the first action that couldn't be replayed
+    error: Option<String>,
+}
+
+// Plays the puzzle's actions in turn, once they run out the puzzle's seat is asked for its answer
+// and every seat plays its first available action to finish the round
+struct PuzzleSeat<'a> {
+    seat: usize,
+    puzzle_seat: usize,
+    agent: &'a dyn Player,
+    replay: &'a Mutex<Replay>,
+}
+
+impl Player for PuzzleSeat<'_> {
+    fn get_action(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex {
+        let first_available = ActionIndex::from_usize(
+            (0..ActionIndex::COUNT)
+                .find(|&i| available_actions[i])
+                .unwrap(),
+        );
+        let mut replay = self.replay.lock().unwrap();
+        if replay.answer.is_some() || replay.error.is_some() {
+            return first_available;
+        }
+        match replay.moves.pop_front() {
+            Some(next) => {
+                if self.seat == self.puzzle_seat {
+                    // keeps a recurrent network's state in step with the round
+                    self.agent.get_action(inputs, available_actions);
+                }
+                match next
+                    .actions()
+                    .into_iter()
+                    .find(|&action| available_actions[action as usize])
+                {
+                    Some(action) => action,
+                    None => {
+                        replay.error = Some(format!(
+                            "{} can't {} at that point",
+                            seat_name(self.seat),
+                            next.name()
+                        ));
+                        first_available
+                    }
+                }
+            }
+            None if self.seat != self.puzzle_seat => {
+                replay.error = Some(format!(
+                    "the actions end on {}'s turn rather than {}'s",
+                    seat_name(self.seat),
+                    seat_name(self.puzzle_seat)
+                ));
+                first_available
+            }
+            None => {
+                let answer = self.agent.get_action(inputs, available_actions);
+                replay.answer = Some(answer);
+                answer
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Takes its action whenever it's available, otherwise the first available one
+    struct Always(ActionIndex);
+
+    impl Player for Always {
+        fn get_action(
+            &self,
+            _inputs: &NeuralNetworkInput,
+            available_actions: &AvailableActions,
+        ) -> ActionIndex {
+            if available_actions[self.0 as usize] {
+                return self.0;
+            }
+            ActionIndex::from_usize(
+                (0..ActionIndex::COUNT)
+                    .find(|&i| available_actions[i])
+                    .unwrap(),
+            )
+        }
+    }
+
+    // North sits left of the dealer holding both bowers, fields go before the hands table
+    fn puzzle(fields: &str) -> Puzzle {
+        toml::from_str(&format!(
+            r#"
+name = "test"
+dealer = "west"
+upcard = "9H"
+{}
+
+[hands]
+north = ["JH", "JD", "AH", "KH", "AS"]
+east = ["9S", "10S", "QS", "9C", "10C"]
+south = ["KS", "JS", "JC", "QC", "KC"]
+west = ["AC", "9D", "10D", "QD", "KD"]
+"#,
+            fields
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn parse_card_reads_rank_then_suit() {
+        assert_eq!(parse_card("JH"), Ok(8));
+        assert_eq!(parse_card("10s"), Ok(1));
+        assert_eq!(parse_card("TS"), Ok(1));
+        assert_eq!(parse_card("ac"), Ok(23));
+        assert_eq!(card_name(8), "JH");
+        for card in ["", "1H", "JX", "H"] {
+            assert!(parse_card(card).is_err(), "{}", card);
+        }
+    }
+
+    #[test]
+    fn moves_parse_and_print_the_same() {
+        assert_eq!(Move::parse("call:hearts"), Ok(Move::Call(1)));
+        assert_eq!(Move::parse("Call_Alone:C"), Ok(Move::CallAlone(3)));
+        assert_eq!(Move::parse("discard:QD"), Ok(Move::Discard(15)));
+        assert!(Move::parse("call").is_err());
+        assert!(Move::parse("order:hearts").is_err());
+        for name in ["order", "order_alone", "pass", "call:clubs", "play:10S"] {
+            assert_eq!(Move::parse(name).unwrap().name(), name);
+        }
+    }
+
+    #[test]
+    fn every_action_is_one_of_its_moves_actions() {
+        for i in 0..ActionIndex::COUNT {
+            let action = ActionIndex::from_usize(i);
+            assert!(Move::from_action(action).actions().contains(&action));
+        }
+    }
+
+    #[test]
+    fn situation_rejects_invalid_puzzles() {
+        assert!(puzzle("seat = \"north\"\naccept = [\"order\"]")
+            .situation()
+            .is_ok());
+        // no answers
+        assert!(puzzle("seat = \"north\"").situation().is_err());
+        assert!(puzzle("seat = \"nowhere\"\naccept = [\"order\"]")
+            .situation()
+            .is_err());
+        // the upcard is also in north's hand
+        let mut dealt_twice = puzzle("seat = \"north\"\naccept = [\"order\"]");
+        dealt_twice.upcard = "JH".to_string();
+        assert!(dealt_twice.situation().is_err());
+        let mut short_hand = puzzle("seat = \"north\"\naccept = [\"order\"]");
+        short_hand.hands.east.pop();
+        assert!(short_hand.situation().is_err());
+    }
+
+    #[test]
+    fn solve_checks_the_answer_against_accept_and_reject() {
+        let order_up = puzzle("seat = \"north\"\naccept = [\"order\", \"order_alone\"]");
+        let result = order_up.solve(&Always(ActionIndex::MakeUpcard)).unwrap();
+        assert!(result.solved);
+        assert_eq!(result.answer, "order");
+        let result = order_up.solve(&Always(ActionIndex::PassUpcard)).unwrap();
+        assert!(!result.solved);
+        assert_eq!(result.answer, "pass");
+
+        let rejected = puzzle("seat = \"north\"\nreject = [\"pass\"]");
+        assert!(
+            rejected
+                .solve(&Always(ActionIndex::MakeUpcard))
+                .unwrap()
+                .solved
+        );
+        assert!(
+            !rejected
+                .solve(&Always(ActionIndex::PassUpcard))
+                .unwrap()
+                .solved
+        );
+    }
+
+    #[test]
+    fn solve_replays_the_actions_before_asking() {
+        // north passes, then east is asked
+        let second_seat = puzzle("seat = \"east\"\nactions = [\"pass\"]\naccept = [\"pass\"]");
+        let result = second_seat.solve(&Always(ActionIndex::MakeUpcard)).unwrap();
+        assert_eq!(result.answer, "order");
+        assert!(!result.solved);
+
+        // every seat passes the upcard, then north calls spades in the second round
+        let second_round = puzzle(
+            "seat = \"north\"\nactions = [\"pass\", \"pass\", \"pass\", \"pass\"]\n\
+             accept = [\"call:spades\"]",
+        );
+        let result = second_round
+            .solve(&Always(ActionIndex::MakeSuitSpade))
+            .unwrap();
+        assert!(result.solved);
+    }
+
+    #[test]
+    fn solve_reports_actions_that_cannot_be_replayed() {
+        let wrong_seat = puzzle("seat = \"south\"\naccept = [\"pass\"]");
+        let error = wrong_seat
+            .solve(&Always(ActionIndex::PassUpcard))
+            .err()
+            .unwrap();
+        assert!(error.contains("rather than south's"), "{}", error);
+
+        let unavailable = puzzle("seat = \"east\"\nactions = [\"play:AS\"]\naccept = [\"pass\"]");
+        let error = unavailable
+            .solve(&Always(ActionIndex::PassUpcard))
+            .err()
+            .unwrap();
+        assert!(error.contains("can't play:AS"), "{}", error);
+    }
+}
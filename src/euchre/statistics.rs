@@ -0,0 +1,184 @@
+use serde_json::{json, Value};
+
+use super::game::RoundSummary;
+
+pub(crate) const SEAT_NAMES: [&str; 4] = ["North", "East", "South", "West"];
+const TEAM_NAMES: [&str; 2] = ["North/South", "East/West"];
+
+// Counts over the rounds played by a seat or a team, calls are counted for the seat that named
+// trump and for its team
+#[derive(Debug, Clone, Default)]
+pub struct PlayStatistics {
+    pub rounds: usize,
+    pub points: usize,
+    pub ordered_up: usize,
+    pub called: usize,
+    pub alone: usize,
+    // calls that scored, the rest were euchred
+    pub calls_made: usize,
+    pub euchres_suffered: usize,
+    pub euchres_inflicted: usize,
+    pub marches_alone: usize,
+    pub leads: usize,
+    pub trump_leads: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GameStatistics {
+    pub games: usize,
+    pub seats: [PlayStatistics; 4],
+    pub teams: [PlayStatistics; 2],
+}
+
+pub(crate) fn rate(count: usize, total: usize) -> f64 {
+    fraction(count as f64, total as f64)
+}
+
+// part of whole, or 0 when whole is 0
+pub(crate) fn fraction(part: f64, whole: f64) -> f64 {
+    if whole == 0.0 {
+        return 0.0;
+    }
+    part / whole
+}
+
+impl PlayStatistics {
+    fn record(&mut self, summary: &RoundSummary, team: usize, includes: impl Fn(usize) -> bool) {
+        self.rounds += 1;
+        self.points += summary.points[team] as usize;
+        if let Some(maker) = summary.maker {
+            if includes(maker) {
+                if summary.ordered_up {
+                    self.ordered_up += 1;
+                } else {
+                    self.called += 1;
+                }
+                // the makers only score nothing when euchred
+                if summary.points[team] > 0 {
+                    self.calls_made += 1;
+                } else {
+                    self.euchres_suffered += 1;
+                }
+                if summary.alone {
+                    self.alone += 1;
+                    if summary.points[team] == 4 {
+                        self.marches_alone += 1;
+                    }
+                }
+            } else if maker % 2 != team && summary.points[team] > 0 {
+                self.euchres_inflicted += 1;
+            }
+        }
+        for &(seat, trump) in summary.leads.iter() {
+            if includes(seat) {
+                self.leads += 1;
+                if trump {
+                    self.trump_leads += 1;
+                }
+            }
+        }
+    }
+
+    pub fn merge(&mut self, other: &PlayStatistics) {
+        self.rounds += other.rounds;
+        self.points += other.points;
+        self.ordered_up += other.ordered_up;
+        self.called += other.called;
+        self.alone += other.alone;
+        self.calls_made += other.calls_made;
+        self.euchres_suffered += other.euchres_suffered;
+        self.euchres_inflicted += other.euchres_inflicted;
+        self.marches_alone += other.marches_alone;
+        self.leads += other.leads;
+        self.trump_leads += other.trump_leads;
+    }
+
+    pub fn calls(&self) -> usize {
+        self.ordered_up + self.called
+    }
+
+    // name and value of each derived statistic, in the order they are shown
+    fn rates(&self) -> [(&'static str, f64); 9] {
+        [
+            ("ordered_up_per_round", rate(self.ordered_up, self.rounds)),
+            ("called_per_round", rate(self.called, self.rounds)),
+            ("alone_per_round", rate(self.alone, self.rounds)),
+            ("call_success_rate", rate(self.calls_made, self.calls())),
+            (
+                "euchres_suffered_per_round",
+                rate(self.euchres_suffered, self.rounds),
+            ),
+            (
+                "euchres_inflicted_per_round",
+                rate(self.euchres_inflicted, self.rounds),
+            ),
+            ("loner_march_rate", rate(self.marches_alone, self.alone)),
+            ("points_per_round", rate(self.points, self.rounds)),
+            ("trump_lead_rate", rate(self.trump_leads, self.leads)),
+        ]
+    }
+
+    fn to_json(&self) -> Value {
+        let mut value = json!({
+            "rounds": self.rounds,
+            "points": self.points,
+            "ordered_up": self.ordered_up,
+            "called": self.called,
+            "alone": self.alone,
+            "calls_made": self.calls_made,
+            "euchres_suffered": self.euchres_suffered,
+            "euchres_inflicted": self.euchres_inflicted,
+            "marches_alone": self.marches_alone,
+            "leads": self.leads,
+            "trump_leads": self.trump_leads,
+        });
+        for (name, rate) in self.rates() {
+            value[name] = json!(rate);
+        }
+        value
+    }
+}
+
+impl GameStatistics {
+    pub fn record(&mut self, summary: &RoundSummary) {
+        for seat in 0..self.seats.len() {
+            self.seats[seat].record(summary, seat % 2, |s| s == seat);
+        }
+        for team in 0..self.teams.len() {
+            self.teams[team].record(summary, team, |s| s % 2 == team);
+        }
+    }
+
+    pub fn print_table(&self) {
+        let columns: Vec<&PlayStatistics> = self.seats.iter().chain(self.teams.iter()).collect();
+        print!("{:<28}", format!("Games: {}", self.games));
+        for name in SEAT_NAMES.iter().chain(TEAM_NAMES.iter()) {
+            print!("{:>12}", name);
+        }
+        println!();
+        print!("{:<28}", "rounds");
+        for column in columns.iter() {
+            print!("{:>12}", column.rounds);
+        }
+        println!();
+        for (i, (name, _)) in columns[0].rates().iter().enumerate() {
+            print!("{:<28}", name);
+            for column in columns.iter() {
+                print!("{:>12.3}", column.rates()[i].1);
+            }
+            println!();
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mut seats = json!({});
+        for (name, statistics) in SEAT_NAMES.iter().zip(self.seats.iter()) {
+            seats[*name] = statistics.to_json();
+        }
+        let mut teams = json!({});
+        for (name, statistics) in TEAM_NAMES.iter().zip(self.teams.iter()) {
+            teams[*name] = statistics.to_json();
+        }
+        json!({ "games": self.games, "seats": seats, "teams": teams })
+    }
+}
@@ -2,7 +2,13 @@ mod cli;
 mod euchre;
 mod organism;
 
-use crate::cli::helpers::*;
+use crate::cli::{helpers::*, options::*};
+use crate::euchre::bots::Bot;
+use crate::organism::{
+    policy::ActionPolicy,
+    quantized::Precision,
+    significance::{IntervalMethod, Sprt},
+};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -15,16 +21,19 @@ struct Cli {
     command: Command,
 }
 
+// Evolve flattens every evolution option, it is only built once per run
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, StructOpt)]
 enum Command {
     #[structopt(about = "Evolve a population of agents")]
     Evolve {
-        #[structopt(short, long)]
-        population_size: Option<usize>,
-        #[structopt(short, long)]
-        generations: Option<usize>,
-        #[structopt(short, long)]
-        thread_count: Option<usize>,
+        #[structopt(flatten)]
+        options: EvolutionOptions,
+        #[structopt(
+            long,
+            help = "TOML or JSON evolution config, overridden by any flags given"
+        )]
+        config: Option<String>,
         #[structopt(short, long)]
         out_dir: Option<String>,
         #[structopt(short, long)]
@@ -44,6 +53,53 @@ enum Command {
         west_player: String,
         #[structopt(short, long)]
         games: Option<usize>,
+        #[structopt(
+            long,
+            help = "argmax, softmax:<temperature> or epsilon_greedy:<epsilon> for every seat"
+        )]
+        policy: Option<ActionPolicy>,
+        #[structopt(long, help = "Write per seat and team statistics to this JSON file")]
+        stats_file: Option<String>,
+        #[structopt(long, help = "wilson or bootstrap 95% interval on the win rate")]
+        interval: Option<IntervalMethod>,
+        #[structopt(
+            long,
+            help = "Stop once one team is significantly stronger, testing win rates of 0.5 \
+                    plus and minus this"
+        )]
+        sprt_delta: Option<f64>,
+        #[structopt(long, help = "Chance of the SPRT picking the wrong team")]
+        sprt_error: Option<f64>,
+        #[structopt(
+            long,
+            help = "Replay every deal with the teams swapping seats, in pairs of games"
+        )]
+        duplicate: bool,
+        #[structopt(short, long)]
+        thread_count: Option<usize>,
+    },
+    #[structopt(about = "Play every pairing of agents and bots and rank them")]
+    League {
+        #[structopt(help = "Agent files")]
+        files: Vec<String>,
+        #[structopt(short, long, help = "Directory of agents to add")]
+        dir: Option<String>,
+        #[structopt(
+            short,
+            long,
+            use_delimiter = true,
+            help = "random or greedy, comma separated"
+        )]
+        bots: Vec<Bot>,
+        #[structopt(short, long, help = "Duplicate pairs of games per pairing")]
+        games: Option<usize>,
+        #[structopt(
+            long,
+            help = "argmax, softmax:<temperature> or epsilon_greedy:<epsilon>"
+        )]
+        policy: Option<ActionPolicy>,
+        #[structopt(short, long)]
+        thread_count: Option<usize>,
     },
     #[structopt(about = "Query an agent for its stats")]
     Stats {
@@ -58,6 +114,48 @@ enum Command {
         second_parent: String,
         #[structopt(short, long)]
         child: String,
+        #[structopt(flatten)]
+        mutation: MutationOptions,
+        #[structopt(
+            long,
+            help = "TOML or JSON evolution config to take mutation settings from"
+        )]
+        config: Option<String>,
+    },
+    #[structopt(about = "Score an agent averaged over many partners")]
+    Robustness {
+        #[structopt(short, long)]
+        agent: String,
+        #[structopt(short, long, help = "Directory of partners, also used as opponents")]
+        partners_dir: String,
+        #[structopt(short, long, help = "Games played with each partner")]
+        games: Option<usize>,
+    },
+    #[structopt(about = "Measure how far apart the networks in a directory are")]
+    Diversity {
+        #[structopt(help = "Directory of networks, such as a gen_N directory")]
+        dir: String,
+        #[structopt(short, long, help = "Positions each network is asked about")]
+        probes: Option<usize>,
+        #[structopt(short, long)]
+        thread_count: Option<usize>,
+    },
+    #[structopt(about = "Score agents on a suite of puzzles")]
+    Puzzles {
+        #[structopt(help = "Puzzle suite, TOML or JSON")]
+        suite: String,
+        #[structopt(help = "Agent files, or bot:<name>")]
+        files: Vec<String>,
+        #[structopt(
+            long,
+            help = "argmax, softmax:<temperature> or epsilon_greedy:<epsilon>"
+        )]
+        policy: Option<ActionPolicy>,
+        #[structopt(
+            long,
+            help = "Exit with an error when an agent solves less than this fraction"
+        )]
+        min_solved: Option<f64>,
     },
     #[structopt(about = "Play with a tutor")]
     Tutor {
@@ -69,6 +167,81 @@ enum Command {
         right: String,
         #[structopt(short, long)]
         ally: String,
+        #[structopt(long, help = "Append your decisions to this records file")]
+        record: Option<String>,
+    },
+    #[structopt(about = "Record an agent's decisions for training")]
+    Record {
+        #[structopt(short, long)]
+        player: String,
+        #[structopt(
+            short,
+            long,
+            help = "East/west opponent, without one the player fills every seat"
+        )]
+        opponent: Option<String>,
+        #[structopt(short, long)]
+        games: Option<usize>,
+        #[structopt(short, long, help = "JSON lines file the decisions are appended to")]
+        records_file: String,
+    },
+    #[structopt(about = "Train an agent on recorded decisions")]
+    Train {
+        #[structopt(short, long, required = true, help = "JSON lines records files")]
+        records_files: Vec<String>,
+        #[structopt(short, long, help = "Agent to start from, random if not given")]
+        initial: Option<String>,
+        #[structopt(short, long)]
+        out_file: String,
+        #[structopt(flatten)]
+        options: TrainingOptions,
+    },
+    #[structopt(about = "Train an agent with self-play policy gradients")]
+    TrainRl {
+        #[structopt(short, long, help = "Agent to start from, random if not given")]
+        initial: Option<String>,
+        #[structopt(short, long)]
+        out_file: String,
+        #[structopt(flatten)]
+        options: ReinforcementOptions,
+    },
+    #[structopt(about = "Measure how often an agent's decisions change under suit relabelling")]
+    Invariance {
+        #[structopt(short, long)]
+        file: String,
+        #[structopt(short, long, help = "Self-play games to collect decisions from")]
+        games: Option<usize>,
+    },
+    #[structopt(about = "Measure an agent's decisions per second")]
+    Benchmark {
+        #[structopt(short, long)]
+        file: String,
+        #[structopt(short, long, help = "Self-play games to collect decisions from")]
+        games: Option<usize>,
+        #[structopt(short, long)]
+        batch_size: Option<usize>,
+    },
+    #[structopt(about = "Export an inference only agent at lower precision")]
+    Quantize {
+        #[structopt(short, long)]
+        file: String,
+        #[structopt(short, long)]
+        out_file: String,
+        #[structopt(short, long, help = "f32 or int8")]
+        precision: Option<Precision>,
+    },
+    #[structopt(about = "Compare a quantised agent's decisions with the original's")]
+    QuantizedAccuracy {
+        #[structopt(short, long)]
+        file: String,
+        #[structopt(short, long)]
+        quantized_file: String,
+        #[structopt(
+            short,
+            long,
+            help = "Self-play games of the original to collect decisions from"
+        )]
+        games: Option<usize>,
     },
 }
 
@@ -77,17 +250,14 @@ fn main() {
 
     match args.command {
         Command::Evolve {
-            population_size,
-            generations,
-            thread_count,
+            options,
+            config,
             out_dir,
             starting_population_dir,
             no_gen_save,
         } => {
             evolve_cli(
-                population_size.unwrap_or_else(|| 4),
-                generations.unwrap_or_else(|| 10),
-                thread_count.unwrap_or_else(|| 1),
+                load_evolution_config(config, &options),
                 out_dir.unwrap_or_else(|| "out".to_string()),
                 starting_population_dir,
                 no_gen_save,
@@ -99,13 +269,63 @@ fn main() {
             south_player,
             west_player,
             games,
+            policy,
+            stats_file,
+            interval,
+            sprt_delta,
+            sprt_error,
+            duplicate,
+            thread_count,
         } => {
+            let games = games.unwrap_or(3);
+            if games == 0 {
+                println!("games must be at least 1");
+                std::process::exit(1);
+            }
+            let sprt = sprt_delta.map(|delta| Sprt {
+                delta,
+                alpha: sprt_error.unwrap_or(0.05),
+                beta: sprt_error.unwrap_or(0.05),
+            });
+            if let Some(Err(e)) = sprt.map(|sprt| sprt.validate()) {
+                println!("{}", e);
+                std::process::exit(1);
+            }
             compete_cli(
                 north_player,
                 east_player,
                 south_player,
                 west_player,
-                games.unwrap_or_else(|| 3),
+                CompeteOptions {
+                    games,
+                    policy: policy.unwrap_or_default(),
+                    stats_file,
+                    interval: interval.unwrap_or(IntervalMethod::Wilson),
+                    sprt,
+                    duplicate,
+                    thread_count: thread_count
+                        .unwrap_or_else(|| {
+                            std::thread::available_parallelism().map_or(1, |count| count.get())
+                        })
+                        .max(1),
+                },
+            );
+        }
+        Command::League {
+            files,
+            dir,
+            bots,
+            games,
+            policy,
+            thread_count,
+        } => {
+            league_cli(
+                files,
+                dir,
+                bots,
+                games.unwrap_or(10),
+                policy.unwrap_or_default(),
+                thread_count.unwrap_or(1).max(1),
             );
         }
         Command::Stats { file } => {
@@ -115,14 +335,100 @@ fn main() {
             first_parent,
             second_parent,
             child,
-        } => breed_cli(first_parent, second_parent, child),
+            mutation,
+            config,
+        } => breed_cli(
+            first_parent,
+            second_parent,
+            child,
+            load_mutation_config(config, &mutation),
+        ),
+        Command::Robustness {
+            agent,
+            partners_dir,
+            games,
+        } => {
+            robustness_cli(agent, partners_dir, games.unwrap_or(10));
+        }
+        Command::Diversity {
+            dir,
+            probes,
+            thread_count,
+        } => {
+            diversity_cli(
+                dir,
+                probes.unwrap_or(200).max(1),
+                thread_count.unwrap_or(1).max(1),
+            );
+        }
+        Command::Puzzles {
+            suite,
+            files,
+            policy,
+            min_solved,
+        } => {
+            puzzles_cli(suite, files, policy.unwrap_or_default(), min_solved);
+        }
         Command::Tutor {
             tutor,
             left,
             right,
             ally,
+            record,
+        } => {
+            tutor_cli(tutor, left, right, ally, record);
+        }
+        Command::Record {
+            player,
+            opponent,
+            games,
+            records_file,
+        } => {
+            record_cli(player, opponent, games.unwrap_or(10), records_file);
+        }
+        Command::Train {
+            records_files,
+            initial,
+            out_file,
+            options,
+        } => {
+            train_cli(
+                records_files,
+                initial,
+                out_file,
+                load_training_config(&options),
+            );
+        }
+        Command::TrainRl {
+            initial,
+            out_file,
+            options,
+        } => {
+            train_rl_cli(initial, out_file, load_reinforcement_config(&options));
+        }
+        Command::Invariance { file, games } => {
+            invariance_cli(file, games.unwrap_or(10));
+        }
+        Command::Benchmark {
+            file,
+            games,
+            batch_size,
+        } => {
+            benchmark_cli(file, games.unwrap_or(10), batch_size.unwrap_or(64).max(1));
+        }
+        Command::Quantize {
+            file,
+            out_file,
+            precision,
+        } => {
+            quantize_cli(file, out_file, precision.unwrap_or(Precision::Int8));
+        }
+        Command::QuantizedAccuracy {
+            file,
+            quantized_file,
+            games,
         } => {
-            tutor_cli(tutor, left, right, ally);
+            quantized_accuracy_cli(file, quantized_file, games.unwrap_or(10));
         }
     };
 }
@@ -0,0 +1,44 @@
+use std::time::Instant;
+
+use super::neural_network::{NeuralNetwork, NeuralNetworkInput};
+
+// Decisions per second of the dense reference, the sparse path and the batched sparse path over
+// the same inputs, checking the three agree
+pub fn benchmark(nn: &NeuralNetwork, inputs: &[NeuralNetworkInput], batch_size: usize) {
+    let start = Instant::now();
+    let reference: Vec<_> = inputs.iter().map(|i| nn.outputs_reference(i)).collect();
+    let reference_rate = inputs.len() as f64 / start.elapsed().as_secs_f64();
+    println!("Reference - Decisions/s: {}", reference_rate);
+
+    let start = Instant::now();
+    let sparse: Vec<_> = inputs.iter().map(|i| nn.outputs(i)).collect();
+    let sparse_rate = inputs.len() as f64 / start.elapsed().as_secs_f64();
+    println!(
+        "Sparse - Decisions/s: {}, Speedup: {}",
+        sparse_rate,
+        sparse_rate / reference_rate
+    );
+
+    let start = Instant::now();
+    let batched: Vec<_> = inputs
+        .chunks(batch_size)
+        .flat_map(|batch| nn.outputs_batch(batch))
+        .collect();
+    let batched_rate = inputs.len() as f64 / start.elapsed().as_secs_f64();
+    println!(
+        "Batched ({}) - Decisions/s: {}, Speedup: {}",
+        batch_size,
+        batched_rate,
+        batched_rate / reference_rate
+    );
+
+    let mut max_difference: f64 = 0.0;
+    for ((reference, sparse), batched) in reference.iter().zip(sparse.iter()).zip(batched.iter()) {
+        for k in 0..reference.len() {
+            max_difference = max_difference
+                .max((reference[k] - sparse[k]).abs())
+                .max((reference[k] - batched[k]).abs());
+        }
+    }
+    println!("Max Output Difference: {}", max_difference);
+}
@@ -0,0 +1,409 @@
+use std::{error::Error, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::euchre::bots::Bot;
+
+use super::curriculum::Curriculum;
+use super::encoding::InputEncoding;
+use super::evaluation::PartnerMode;
+use super::metrics::MetricsFormat;
+use super::mutation::{MutationDistribution, MutationParameters, MutationSchedule};
+use super::neural_network::DEFAULT_HIDDEN_NODES;
+use super::novelty::SearchMode;
+use super::policy::ActionPolicy;
+use super::selection::SelectionScheme;
+use super::tournament::TournamentFormat;
+
+pub const VALID_POPULATION_SIZES: [usize; 10] = [2048, 1024, 512, 256, 128, 64, 32, 16, 8, 4];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EvolutionConfig {
+    pub population_size: usize,
+    pub generations: usize,
+    pub thread_count: usize,
+    pub network: NetworkConfig,
+    pub init: InitConfig,
+    pub mutation: MutationConfig,
+    pub evaluation: EvaluationConfig,
+    pub selection: SelectionConfig,
+    // what fitness rewards besides winning, and where parents come from
+    pub search: SearchMode,
+    // what organisms are evaluated on, whole games or a single part of the game
+    pub curriculum: Curriculum,
+    pub tournament: TournamentConfig,
+    pub metrics: MetricsConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkConfig {
+    // widths of the hidden layers from input to output
+    pub hidden_layers: Vec<usize>,
+    // feed the first hidden layer back into itself and only show the latest events
+    pub recurrent: bool,
+    pub encoding: InputEncoding,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InitConfig {
+    pub weight_range: f64,
+    pub bias_range: f64,
+    pub connection_probability: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MutationConfig {
+    pub rate: f64,
+    pub magnitude: f64,
+    // per gene class overrides of rate and magnitude
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bias_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activation_rate: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bias_magnitude: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight_magnitude: Option<f64>,
+    pub distribution: MutationDistribution,
+    pub schedule: MutationSchedule,
+    // evolve per node step sizes stored in the genome instead of using the magnitudes
+    pub self_adaptive: bool,
+    pub min_step_size: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EvaluationConfig {
+    pub games_per_match: usize,
+    // times the population is paired off for matches, fitness is the fraction of matches won
+    pub rounds: usize,
+    pub partner_mode: PartnerMode,
+    // how organisms pick their actions during matches, a stochastic policy explores more lines
+    // of play than argmax
+    pub policy: ActionPolicy,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SelectionConfig {
+    pub scheme: SelectionScheme,
+    pub breeding_fraction: f64,
+    pub survivor_fraction: f64,
+}
+
+// The tournament after the last generation that picks the champion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TournamentConfig {
+    pub format: TournamentFormat,
+    pub games_per_match: usize,
+    // organisms saved from the top of the standings
+    pub top_k: usize,
+}
+
+// The per generation metrics log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsConfig {
+    pub format: MetricsFormat,
+    // bots every organism plays each generation, self partnered, to track progress against a
+    // fixed level of play
+    pub baselines: Vec<Bot>,
+    pub baseline_games: usize,
+    // positions every organism is asked about to measure behavioural diversity, 0 to skip
+    // measuring diversity
+    pub diversity_probes: usize,
+    // mean fraction of probes two organisms disagree on below which the population is reported
+    // as converged
+    pub convergence_threshold: f64,
+}
+
+impl Default for EvolutionConfig {
+    fn default() -> Self {
+        EvolutionConfig {
+            population_size: 4,
+            generations: 10,
+            thread_count: 1,
+            network: NetworkConfig::default(),
+            init: InitConfig::default(),
+            mutation: MutationConfig::default(),
+            evaluation: EvaluationConfig::default(),
+            selection: SelectionConfig::default(),
+            search: SearchMode::Objective,
+            curriculum: Curriculum::FullGame,
+            tournament: TournamentConfig::default(),
+            metrics: MetricsConfig::default(),
+        }
+    }
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            hidden_layers: vec![DEFAULT_HIDDEN_NODES],
+            recurrent: false,
+            encoding: InputEncoding::Absolute,
+        }
+    }
+}
+
+impl Default for InitConfig {
+    fn default() -> Self {
+        InitConfig {
+            weight_range: 0.5,
+            bias_range: 0.5,
+            connection_probability: 0.5,
+        }
+    }
+}
+
+impl Default for MutationConfig {
+    fn default() -> Self {
+        MutationConfig {
+            rate: 0.01,
+            magnitude: 0.1,
+            bias_rate: None,
+            weight_rate: None,
+            connection_rate: None,
+            activation_rate: None,
+            bias_magnitude: None,
+            weight_magnitude: None,
+            distribution: MutationDistribution::Uniform,
+            schedule: MutationSchedule::Constant,
+            self_adaptive: false,
+            min_step_size: 0.0001,
+        }
+    }
+}
+
+impl Default for EvaluationConfig {
+    fn default() -> Self {
+        EvaluationConfig {
+            games_per_match: 3,
+            rounds: 1,
+            partner_mode: PartnerMode::SelfPartner,
+            policy: ActionPolicy::Argmax,
+        }
+    }
+}
+
+impl Default for SelectionConfig {
+    fn default() -> Self {
+        SelectionConfig {
+            scheme: SelectionScheme::Truncation,
+            breeding_fraction: 0.5,
+            survivor_fraction: 0.5,
+        }
+    }
+}
+
+impl Default for TournamentConfig {
+    fn default() -> Self {
+        TournamentConfig {
+            format: TournamentFormat::SingleElimination,
+            games_per_match: 3,
+            top_k: 1,
+        }
+    }
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        MetricsConfig {
+            format: MetricsFormat::Jsonl,
+            baselines: Vec::new(),
+            baseline_games: 20,
+            diversity_probes: 200,
+            convergence_threshold: 0.02,
+        }
+    }
+}
+
+impl EvolutionConfig {
+    // .toml files are read as TOML, anything else as JSON, missing fields keep their defaults
+    pub fn load_from_file(filename: &str) -> Result<EvolutionConfig, Box<dyn Error>> {
+        let contents = fs::read_to_string(filename)?;
+        let config = match Path::new(filename).extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&contents)?,
+            _ => serde_json::from_str(&contents)?,
+        };
+        Ok(config)
+    }
+
+    pub fn save_to_file(&self, filename: &str) -> Result<(), Box<dyn Error>> {
+        let contents = match Path::new(filename).extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::to_string_pretty(self)?,
+            _ => serde_json::to_string_pretty(self)?,
+        };
+        fs::write(filename, contents)?;
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !VALID_POPULATION_SIZES.contains(&self.population_size) {
+            return Err(format!(
+                "Invalid population size, valid sizes are: {:?}",
+                VALID_POPULATION_SIZES
+            ));
+        }
+        if self.thread_count == 0 {
+            return Err("thread_count must be at least 1".to_string());
+        }
+        if self.network.hidden_layers.contains(&0) {
+            return Err("network.hidden_layers must not contain empty layers".to_string());
+        }
+        if self.network.recurrent && self.network.hidden_layers.is_empty() {
+            return Err("a recurrent network needs at least one hidden layer".to_string());
+        }
+        if self.network.recurrent && self.network.encoding != InputEncoding::Absolute {
+            return Err("a recurrent network only takes the absolute encoding".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.init.connection_probability) {
+            return Err("init.connection_probability must be within [0, 1]".to_string());
+        }
+        if self.init.weight_range <= 0.0 || self.init.bias_range <= 0.0 {
+            return Err("init.weight_range and init.bias_range must be positive".to_string());
+        }
+        self.mutation.validate()?;
+        if self.evaluation.games_per_match.is_multiple_of(2) {
+            return Err("evaluation.games_per_match must be odd".to_string());
+        }
+        if self.evaluation.rounds == 0 {
+            return Err("evaluation.rounds must be at least 1".to_string());
+        }
+        self.evaluation
+            .policy
+            .validate()
+            .map_err(|e| format!("evaluation.{}", e))?;
+        self.selection.scheme.validate(self.population_size)?;
+        if !(0.0..=1.0).contains(&self.selection.breeding_fraction)
+            || !(0.0..=1.0).contains(&self.selection.survivor_fraction)
+        {
+            return Err(
+                "selection.breeding_fraction and selection.survivor_fraction must be within [0, 1]"
+                    .to_string(),
+            );
+        }
+        let breeders = self.breeder_count();
+        if self.selection.scheme.uses_breeding_pool() && breeders < 2 {
+            return Err(format!(
+                "selection.breeding_fraction of {} leaves {} breeders out of {}, need at least 2",
+                self.selection.breeding_fraction, breeders, self.population_size
+            ));
+        }
+        if self.survivor_count() >= self.population_size {
+            return Err(format!(
+                "{} of {} organisms survive each generation, leaving no room for children",
+                self.survivor_count(),
+                self.population_size
+            ));
+        }
+        self.search.validate()?;
+        self.curriculum.validate()?;
+        self.tournament
+            .format
+            .validate(self.population_size)
+            .map_err(|e| format!("tournament.{}", e))?;
+        if self.tournament.games_per_match.is_multiple_of(2) {
+            return Err("tournament.games_per_match must be odd".to_string());
+        }
+        if self.tournament.top_k == 0 || self.tournament.top_k > self.population_size {
+            return Err(format!(
+                "tournament.top_k must be within [1, {}]",
+                self.population_size
+            ));
+        }
+        if !self.metrics.baselines.is_empty() && self.metrics.baseline_games == 0 {
+            return Err("metrics.baseline_games must be at least 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.metrics.convergence_threshold) {
+            return Err("metrics.convergence_threshold must be within [0, 1]".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn breeder_count(&self) -> usize {
+        (self.population_size as f64 * self.selection.breeding_fraction).round() as usize
+    }
+
+    pub fn survivor_count(&self) -> usize {
+        self.selection.scheme.survivor_count(
+            self.population_size,
+            self.selection.survivor_fraction,
+            self.breeder_count(),
+        )
+    }
+}
+
+impl MutationConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, rate) in [
+            ("rate", Some(self.rate)),
+            ("bias_rate", self.bias_rate),
+            ("weight_rate", self.weight_rate),
+            ("connection_rate", self.connection_rate),
+            ("activation_rate", self.activation_rate),
+        ] {
+            if let Some(rate) = rate {
+                if !(0.0..=1.0).contains(&rate) {
+                    return Err(format!("mutation.{} must be within [0, 1]", name));
+                }
+            }
+        }
+        for (name, magnitude) in [
+            ("magnitude", Some(self.magnitude)),
+            ("bias_magnitude", self.bias_magnitude),
+            ("weight_magnitude", self.weight_magnitude),
+        ] {
+            if let Some(magnitude) = magnitude {
+                if magnitude <= 0.0 {
+                    return Err(format!("mutation.{} must be positive", name));
+                }
+            }
+        }
+        match self.schedule {
+            MutationSchedule::Constant => (),
+            MutationSchedule::Linear { final_scale } => {
+                if final_scale < 0.0 {
+                    return Err("mutation.schedule.final_scale must not be negative".to_string());
+                }
+            }
+            MutationSchedule::Exponential { decay } => {
+                if decay <= 0.0 {
+                    return Err("mutation.schedule.decay must be positive".to_string());
+                }
+            }
+        }
+        if self.min_step_size <= 0.0 {
+            return Err("mutation.min_step_size must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn parameters(&self, generation: usize, generations: usize) -> MutationParameters {
+        let scale = self.schedule.scale(generation, generations);
+        let rate = |class_rate: Option<f64>| (class_rate.unwrap_or(self.rate) * scale).min(1.0);
+        let magnitude = |class_magnitude: Option<f64>| {
+            (class_magnitude.unwrap_or(self.magnitude) * scale).max(self.min_step_size)
+        };
+        MutationParameters {
+            bias_rate: rate(self.bias_rate),
+            weight_rate: rate(self.weight_rate),
+            connection_rate: rate(self.connection_rate),
+            activation_rate: rate(self.activation_rate),
+            bias_magnitude: magnitude(self.bias_magnitude),
+            weight_magnitude: magnitude(self.weight_magnitude),
+            distribution: self.distribution,
+            self_adaptive: self.self_adaptive,
+            min_step_size: self.min_step_size,
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use std::str::FromStr;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use strum::EnumCount;
+
+use crate::euchre::{
+    bots::Bot,
+    enums::ActionIndex,
+    game::{play_round, RoundSummary},
+    player::Player,
+    statistics::{GameStatistics, PlayStatistics},
+};
+
+use super::evaluation::play_baseline;
+use super::neural_network::{AvailableActions, NeuralNetwork, NeuralNetworkInput, Seat};
+use super::policy::ActionPolicy;
+use super::training::DecisionRecord;
+
+// Bots the opponents curriculum moves through before the population plays itself
+pub const OPPONENT_LADDER: [Bot; 2] = [Bot::Random, Bot::Greedy];
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Curriculum {
+    // whole games in self partnered matches within the population
+    #[default]
+    FullGame,
+    // trick play alone, the seat left of the dealer always orders up the upcard and the organism
+    // plays out the tricks against greedy bots, as makers and as defenders on each deal. Fitness
+    // is the fraction of tricks taken
+    TrickPlay {
+        rounds: usize,
+    },
+    // bidding alone against greedy bots, with greedy bots playing every seat's cards once trump
+    // is named, on each deal as the dealer's team and the other team. Fitness is the points won
+    // less the points given up per round, scaled into [0, 1]
+    Bidding {
+        rounds: usize,
+    },
+    // whole games against each bot of the ladder in turn, moving to the next once the population's
+    // mean win rate reaches promotion_win_rate, then matches within the population
+    Opponents {
+        games: usize,
+        promotion_win_rate: f64,
+    },
+}
+
+// How an organism did on its curriculum task
+pub struct TaskResult {
+    pub fitness: f64,
+    pub statistics: PlayStatistics,
+    // the first decision with NaN or infinite outputs in any of the organism's seats
+    pub non_finite: Option<DecisionRecord>,
+}
+
+// Orders up the upcard at the first chance, so the seat left of the dealer always makes trump
+struct OrderUp;
+
+impl Player for OrderUp {
+    fn get_action(
+        &self,
+        _inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex {
+        if available_actions[ActionIndex::MakeUpcard as usize] {
+            return ActionIndex::MakeUpcard;
+        }
+        ActionIndex::from_usize(
+            (0..ActionIndex::COUNT)
+                .find(|&i| available_actions[i])
+                .unwrap(),
+        )
+    }
+}
+
+impl FromStr for Curriculum {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowered = s.to_lowercase();
+        let (name, parameter) = match lowered.split_once(':') {
+            Some((name, parameter)) => (name, Some(parameter)),
+            None => (lowered.as_str(), None),
+        };
+        let rounds = || match parameter {
+            Some(parameter) => parameter
+                .parse::<usize>()
+                .map_err(|_| format!("invalid curriculum parameter {}", parameter)),
+            None => Ok(32),
+        };
+        let curriculum = match name {
+            "full_game" => Curriculum::FullGame,
+            "trick_play" => Curriculum::TrickPlay { rounds: rounds()? },
+            "bidding" => Curriculum::Bidding { rounds: rounds()? },
+            "opponents" => Curriculum::Opponents {
+                games: 20,
+                promotion_win_rate: match parameter {
+                    Some(parameter) => parameter
+                        .parse::<f64>()
+                        .map_err(|_| format!("invalid curriculum parameter {}", parameter))?,
+                    None => 0.6,
+                },
+            },
+            _ => return Err(format!("unknown curriculum {}", s)),
+        };
+        curriculum.validate()?;
+        Ok(curriculum)
+    }
+}
+
+impl Curriculum {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Curriculum::TrickPlay { rounds: 0 } | Curriculum::Bidding { rounds: 0 } => {
+                Err("curriculum.rounds must be at least 1".to_string())
+            }
+            Curriculum::Opponents { games: 0, .. } => {
+                Err("curriculum.games must be at least 1".to_string())
+            }
+            Curriculum::Opponents {
+                promotion_win_rate, ..
+            } if !(0.0..=1.0).contains(&promotion_win_rate) => {
+                Err("curriculum.promotion_win_rate must be within [0, 1]".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Whether fitness at this level comes from matches within the population
+    pub fn plays_matches(&self, level: usize) -> bool {
+        match self {
+            Curriculum::FullGame => true,
+            Curriculum::Opponents { .. } => level >= OPPONENT_LADDER.len(),
+            _ => false,
+        }
+    }
+
+    pub fn task(&self, level: usize) -> String {
+        match self {
+            Curriculum::FullGame => "full_game".to_string(),
+            Curriculum::TrickPlay { .. } => "trick_play".to_string(),
+            Curriculum::Bidding { .. } => "bidding".to_string(),
+            Curriculum::Opponents { .. } => match OPPONENT_LADDER.get(level) {
+                Some(bot) => format!("opponent_{}", bot.name()),
+                None => "full_game".to_string(),
+            },
+        }
+    }
+
+    // Deals for this generation's task, every organism plays the same ones so their fitness can
+    // be compared
+    pub fn deal_seeds<R: Rng>(&self, rng: &mut R) -> Vec<u64> {
+        match *self {
+            Curriculum::TrickPlay { rounds } | Curriculum::Bidding { rounds } => {
+                (0..rounds).map(|_| rng.gen()).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    // The level to play at next generation given this generation's mean fitness
+    pub fn next_level(&self, level: usize, mean_fitness: f64) -> usize {
+        match *self {
+            Curriculum::Opponents {
+                promotion_win_rate, ..
+            } if level < OPPONENT_LADDER.len() && mean_fitness >= promotion_win_rate => level + 1,
+            _ => level,
+        }
+    }
+
+    // Fitness of a network on the task at this level, for any level that doesn't play matches
+    pub fn evaluate(
+        &self,
+        level: usize,
+        nn: &NeuralNetwork,
+        deal_seeds: &[u64],
+        policy: ActionPolicy,
+    ) -> TaskResult {
+        match *self {
+            Curriculum::TrickPlay { .. } => play_deals(
+                nn,
+                policy,
+                deal_seeds,
+                |rng, players| play_round([&OrderUp as &dyn Player; 4], players, rng),
+                |summary, team| summary.tricks[team] as f64 / 5.0,
+            ),
+            Curriculum::Bidding { .. } => play_deals(
+                nn,
+                policy,
+                deal_seeds,
+                |rng, bidders| play_round(bidders, [&Bot::Greedy as &dyn Player; 4], rng),
+                |summary, team| {
+                    let net = summary.points[team] as f64 - summary.points[1 - team] as f64;
+                    (net + 4.0) / 8.0
+                },
+            ),
+            Curriculum::Opponents { games, .. } => {
+                let result = play_baseline(nn, OPPONENT_LADDER[level], games, policy);
+                TaskResult {
+                    fitness: result.win_rate,
+                    statistics: result.statistics,
+                    non_finite: result.non_finite,
+                }
+            }
+            Curriculum::FullGame => panic!("full games are played as matches"),
+        }
+    }
+}
+
+// The organism's two seats in team's positions, counted from the dealer, and the bot in the others
+fn seats<'a>(organism: &'a [Seat; 2], bot: &'a dyn Player, team: usize) -> [&'a dyn Player; 4] {
+    [0, 1, 2, 3].map(|position| {
+        if position % 2 == team {
+            &organism[position / 2] as &dyn Player
+        } else {
+            bot
+        }
+    })
+}
+
+// Plays every deal once for each team, with a fresh seat for each of the organism's positions,
+// and averages the fitness of each round, statistics only count the organism's seats
+fn play_deals(
+    nn: &NeuralNetwork,
+    policy: ActionPolicy,
+    deal_seeds: &[u64],
+    play: impl Fn(&mut StdRng, [&dyn Player; 4]) -> RoundSummary,
+    fitness_of: impl Fn(&RoundSummary, usize) -> f64,
+) -> TaskResult {
+    let mut fitness = 0.0;
+    let mut played = PlayStatistics::default();
+    let mut non_finite = None;
+    for &seed in deal_seeds.iter() {
+        for team in 0..2 {
+            let organism = [nn.seat_with_policy(policy), nn.seat_with_policy(policy)];
+            let summary = play(
+                &mut StdRng::seed_from_u64(seed),
+                seats(&organism, &Bot::Greedy, team),
+            );
+            fitness += fitness_of(&summary, team);
+            let mut statistics = GameStatistics::default();
+            statistics.record(&summary);
+            played.merge(&statistics.seats[team]);
+            played.merge(&statistics.seats[team + 2]);
+            if non_finite.is_none() {
+                non_finite = organism.iter().find_map(|seat| seat.non_finite_decision());
+            }
+        }
+    }
+    TaskResult {
+        fitness: fitness / (2 * deal_seeds.len()) as f64,
+        statistics: played,
+        non_finite,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Whether the seat is this player, comparing addresses since seats hold no identity
+    fn is(seat: &dyn Player, player: &dyn Player) -> bool {
+        std::ptr::addr_eq(seat as *const dyn Player, player as *const dyn Player)
+    }
+
+    #[test]
+    fn seats_give_each_partner_position_its_own_seat() {
+        let nn = NeuralNetwork::new();
+        let organism = [
+            nn.seat_with_policy(ActionPolicy::Argmax),
+            nn.seat_with_policy(ActionPolicy::Argmax),
+        ];
+        for team in 0..2 {
+            let players = seats(&organism, &Bot::Greedy, team);
+            assert!(is(players[team], &organism[0]));
+            assert!(is(players[team + 2], &organism[1]));
+            assert!(!is(players[team], players[team + 2]));
+            assert!(is(players[1 - team], &Bot::Greedy));
+            assert!(is(players[3 - team], &Bot::Greedy));
+        }
+    }
+
+    #[test]
+    fn task_levels_are_scored_on_the_same_deals() {
+        let nn = NeuralNetwork::new();
+        let deal_seeds: Vec<u64> = (0..4).collect();
+        for curriculum in [
+            Curriculum::TrickPlay { rounds: 4 },
+            Curriculum::Bidding { rounds: 4 },
+        ] {
+            let first = curriculum.evaluate(0, &nn, &deal_seeds, ActionPolicy::Argmax);
+            let second = curriculum.evaluate(0, &nn, &deal_seeds, ActionPolicy::Argmax);
+            assert!((0.0..=1.0).contains(&first.fitness));
+            assert_eq!(first.fitness, second.fitness);
+            assert!(first.non_finite.is_none());
+        }
+    }
+
+    #[test]
+    fn opponents_move_up_the_ladder_at_the_promotion_win_rate() {
+        let curriculum = Curriculum::from_str("opponents:0.6").unwrap();
+        assert!(!curriculum.plays_matches(0));
+        assert_eq!(curriculum.next_level(0, 0.5), 0);
+        assert_eq!(curriculum.next_level(0, 0.6), 1);
+        assert_eq!(curriculum.next_level(1, 0.7), OPPONENT_LADDER.len());
+        assert!(curriculum.plays_matches(OPPONENT_LADDER.len()));
+        assert_eq!(
+            curriculum.next_level(OPPONENT_LADDER.len(), 1.0),
+            OPPONENT_LADDER.len()
+        );
+    }
+}
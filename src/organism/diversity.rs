@@ -0,0 +1,156 @@
+use std::{collections::HashSet, sync::Mutex};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::Serialize;
+use strum::EnumCount;
+
+use crate::euchre::{
+    bots::Bot, enums::ActionIndex, game::play_euchre_with_summaries, player::Player,
+};
+
+use super::neural_network::{AvailableActions, NeuralNetwork, NeuralNetworkInput};
+use super::training::{DecisionRecord, RecordingPlayer};
+
+// Probe positions are the same on every run so distances can be compared between runs
+pub const PROBE_SEED: u64 = 0x5eed;
+// Pairs sampled when measuring a population, all pairs are used in smaller populations
+const MAX_PAIRS: usize = 1024;
+
+// Differences between two networks of the same shape
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct GenomeDistance {
+    // root mean square difference of the weights and biases, disabled connections count as zero
+    pub weight: f64,
+    // fraction of connections enabled in one network and not the other
+    pub connection: f64,
+    // fraction of nodes with different activation functions
+    pub activation: f64,
+}
+
+// Mean distances over pairs of networks in a population
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct PopulationDiversity {
+    pub weight_distance: f64,
+    pub connection_distance: f64,
+    pub activation_distance: f64,
+    // fraction of probe positions where the two networks choose different actions
+    pub behavioural_distance: f64,
+    // different sequences of actions chosen over the probe positions
+    pub distinct_behaviours: usize,
+}
+
+// Plays uniformly random available actions from a seeded generator
+struct SeededRandomPlayer {
+    rng: Mutex<StdRng>,
+}
+
+impl Player for SeededRandomPlayer {
+    fn get_action(
+        &self,
+        _inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex {
+        let available: Vec<usize> = (0..ActionIndex::COUNT)
+            .filter(|&i| available_actions[i])
+            .collect();
+        let mut rng = self.rng.lock().unwrap();
+        ActionIndex::from_usize(available[rng.gen_range(0..available.len())])
+    }
+}
+
+// Positions met by greedy bots playing against random ones, the same for a given seed
+pub fn probe_positions(count: usize, seed: u64) -> Vec<DecisionRecord> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let random = SeededRandomPlayer {
+        rng: Mutex::new(StdRng::seed_from_u64(seed.wrapping_add(1))),
+    };
+    let mut probes = Vec::with_capacity(count);
+    while probes.len() < count {
+        let players = [
+            RecordingPlayer::new(&Bot::Greedy),
+            RecordingPlayer::new(&random),
+            RecordingPlayer::new(&Bot::Greedy),
+            RecordingPlayer::new(&random),
+        ];
+        play_euchre_with_summaries(
+            &players[0],
+            &players[1],
+            &players[2],
+            &players[3],
+            &mut rng,
+            &mut |_| (),
+        );
+        for player in players.iter() {
+            probes.extend(player.take_records());
+        }
+    }
+    probes.truncate(count);
+    probes
+}
+
+// The action a network chooses at each probe, a recurrent network sees each one as the first
+// decision of a round
+pub fn probe_actions(nn: &NeuralNetwork, probes: &[DecisionRecord]) -> Vec<usize> {
+    probes
+        .iter()
+        .map(|probe| {
+            nn.seat()
+                .get_action(&probe.inputs(), &probe.available_actions()) as usize
+        })
+        .collect()
+}
+
+pub fn behavioural_distance(actions: &[usize], other_actions: &[usize]) -> f64 {
+    let different = actions
+        .iter()
+        .zip(other_actions.iter())
+        .filter(|(a, b)| a != b)
+        .count();
+    different as f64 / actions.len() as f64
+}
+
+// Every pair of networks, or a sample seeded like the probes so the same population always
+// measures the same
+fn pairs(count: usize) -> Vec<(usize, usize)> {
+    if count * count.saturating_sub(1) / 2 <= MAX_PAIRS {
+        return (0..count)
+            .flat_map(|i| (i + 1..count).map(move |j| (i, j)))
+            .collect();
+    }
+    let mut rng = StdRng::seed_from_u64(PROBE_SEED);
+    (0..MAX_PAIRS)
+        .map(|_| {
+            let i = rng.gen_range(0..count);
+            (i, (i + rng.gen_range(1..count)) % count)
+        })
+        .collect()
+}
+
+// Mean genome and behavioural distances over every pair of networks, or a sample of pairs in
+// large populations, probes are played in parallel on the current thread pool
+pub fn population_diversity(
+    networks: &[&NeuralNetwork],
+    probes: &[DecisionRecord],
+) -> Result<PopulationDiversity, String> {
+    let actions: Vec<Vec<usize>> = networks
+        .par_iter()
+        .map(|nn| probe_actions(nn, probes))
+        .collect();
+    let pairs = pairs(networks.len());
+    let mut diversity = PopulationDiversity::default();
+    for &(i, j) in pairs.iter() {
+        let distance = networks[i].genome_distance(networks[j])?;
+        diversity.weight_distance += distance.weight;
+        diversity.connection_distance += distance.connection;
+        diversity.activation_distance += distance.activation;
+        diversity.behavioural_distance += behavioural_distance(&actions[i], &actions[j]);
+    }
+    let pair_count = pairs.len().max(1) as f64;
+    diversity.weight_distance /= pair_count;
+    diversity.connection_distance /= pair_count;
+    diversity.activation_distance /= pair_count;
+    diversity.behavioural_distance /= pair_count;
+    diversity.distinct_behaviours = actions.iter().collect::<HashSet<_>>().len();
+    Ok(diversity)
+}
@@ -0,0 +1,319 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use strum::EnumCount;
+
+use crate::euchre::enums::{ActionIndex, StateIndex};
+
+use super::neural_network::NeuralNetworkInput;
+
+// Everything before the trick history: score, dealer, trick counts, upcard, hand, bids and trump
+const CONTEXT_COUNT: usize = StateIndex::Trick1MyselfLead as usize;
+// Each trick is 4 lead positions, 4 lead suits, then 4 cards played of 24 each
+pub(crate) const TRICK_SLOT_COUNT: usize = 104;
+pub(crate) const TRICK_LEAD_COUNT: usize = 4;
+const TRICK_CARD_OFFSET: usize = 8;
+pub(crate) const CARD_COUNT: usize = 24;
+const CARDS_PER_TRICK: usize = 4;
+
+pub const EVENT_INPUT_COUNT: usize =
+    CONTEXT_COUNT + CARDS_PER_TRICK * CARD_COUNT + TRICK_LEAD_COUNT;
+
+pub(crate) const SUIT_COUNT: usize = 4;
+pub(crate) const RANK_COUNT: usize = 6;
+// rank offsets within a suit, from Nine to Ace
+const JACK: usize = 2;
+const ACE: usize = 5;
+const TRUMP_CARD_COUNT: usize = 7;
+const NEXT_CARD_COUNT: usize = 5;
+const OFF_CARD_COUNT: usize = 6;
+pub(crate) const BID_SUIT_SLOT_COUNT: usize = 9;
+const RELATIVE_POSITION_COUNT: usize = 4;
+
+pub const SUIT_RELATIVE_INPUT_COUNT: usize = StateIndex::UpcardSpadeNine as usize
+    + 2 * CARD_COUNT
+    + (StateIndex::BidSuitMyselfMakeSpade as usize - StateIndex::BidUpcardMyselfMake as usize)
+    + RELATIVE_POSITION_COUNT * BID_SUIT_SLOT_COUNT
+    + 1
+    + CARD_COUNT
+    + TRICK_LEAD_COUNT
+    + SUIT_COUNT
+    + 2 * CARD_COUNT
+    + RELATIVE_POSITION_COUNT
+    + SUIT_COUNT
+    + (RELATIVE_POSITION_COUNT - 1) * SUIT_COUNT;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputEncoding {
+    // the StateIndex inputs as they are
+    Absolute,
+    // cards and suits relative to trump, or to the upcard while bidding, plus derived features
+    SuitRelative,
+}
+
+// Suits relative to trump: trump, next (same colour), then the two other suits with the longer
+// one in hand first
+struct SuitFrame {
+    suits: [usize; SUIT_COUNT],
+    relative: [usize; SUIT_COUNT],
+}
+
+impl FromStr for InputEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "absolute" => Ok(InputEncoding::Absolute),
+            "suit_relative" => Ok(InputEncoding::SuitRelative),
+            _ => Err(format!("unknown input encoding {}", s)),
+        }
+    }
+}
+
+impl InputEncoding {
+    pub fn from_u8(value: u8) -> Option<InputEncoding> {
+        match value {
+            0 => Some(InputEncoding::Absolute),
+            1 => Some(InputEncoding::SuitRelative),
+            _ => None,
+        }
+    }
+
+    pub fn input_count(&self) -> usize {
+        match self {
+            InputEncoding::Absolute => StateIndex::COUNT,
+            InputEncoding::SuitRelative => SUIT_RELATIVE_INPUT_COUNT,
+        }
+    }
+
+    // The inputs of the first layer, and the ActionIndex each output stands for
+    pub fn encode(&self, inputs: &NeuralNetworkInput) -> (Vec<f64>, [usize; ActionIndex::COUNT]) {
+        match self {
+            InputEncoding::Absolute => (inputs.to_vec(), std::array::from_fn(|i| i)),
+            InputEncoding::SuitRelative => {
+                let frame = SuitFrame::new(inputs);
+                (encode_suit_relative(inputs, &frame), frame.actions())
+            }
+        }
+    }
+}
+
+impl SuitFrame {
+    fn new(inputs: &NeuralNetworkInput) -> Self {
+        let trump = (0..SUIT_COUNT)
+            .find(|&s| inputs[StateIndex::TrumpSuitSpade as usize + s] != 0.0)
+            .or_else(|| {
+                (0..CARD_COUNT)
+                    .find(|&c| inputs[StateIndex::UpcardSpadeNine as usize + c] != 0.0)
+                    .map(|c| c / RANK_COUNT)
+            })
+            .unwrap_or(0);
+        let hand_count = |s: usize| {
+            (0..RANK_COUNT)
+                .filter(|&r| inputs[StateIndex::HandSpadeNine as usize + s * RANK_COUNT + r] != 0.0)
+                .count()
+        };
+        let mut off_suits: Vec<usize> = (0..SUIT_COUNT)
+            .filter(|&s| s != trump && s != next_suit(trump))
+            .collect();
+        off_suits.sort_by_key(|&s| std::cmp::Reverse(hand_count(s)));
+        let suits = [trump, next_suit(trump), off_suits[0], off_suits[1]];
+        let mut relative = [0; SUIT_COUNT];
+        for (q, &s) in suits.iter().enumerate() {
+            relative[s] = q;
+        }
+        SuitFrame { suits, relative }
+    }
+
+    // Right bower, left bower, trump A K Q 10 9, next A K Q 10 9, then each other suit A to 9
+    fn card(&self, card: usize) -> usize {
+        let (suit, rank) = (card / RANK_COUNT, card % RANK_COUNT);
+        let without_jack = |rank: usize| match rank {
+            ACE => 0,
+            4 => 1,
+            3 => 2,
+            1 => 3,
+            _ => 4,
+        };
+        match (self.relative[suit], rank) {
+            (0, JACK) => 0,
+            (1, JACK) => 1,
+            (0, _) => 2 + without_jack(rank),
+            (1, _) => TRUMP_CARD_COUNT + without_jack(rank),
+            (q, _) => TRUMP_CARD_COUNT + NEXT_CARD_COUNT + (q - 2) * OFF_CARD_COUNT + (ACE - rank),
+        }
+    }
+
+    fn actions(&self) -> [usize; ActionIndex::COUNT] {
+        let mut actions: [usize; ActionIndex::COUNT] = std::array::from_fn(|i| i);
+        for card in 0..CARD_COUNT {
+            let relative = self.card(card);
+            actions[ActionIndex::PlaySpadeNine as usize + relative] =
+                ActionIndex::PlaySpadeNine as usize + card;
+            actions[ActionIndex::DiscardSpadeNine as usize + relative] =
+                ActionIndex::DiscardSpadeNine as usize + card;
+        }
+        for (q, &s) in self.suits.iter().enumerate() {
+            actions[ActionIndex::MakeSuitSpade as usize + q] =
+                ActionIndex::MakeSuitSpade as usize + s;
+            actions[ActionIndex::MakeSuitSpadeAlone as usize + q] =
+                ActionIndex::MakeSuitSpadeAlone as usize + s;
+        }
+        actions
+    }
+}
+
+fn next_suit(suit: usize) -> usize {
+    // Spade and Club, Heart and Diamond
+    SUIT_COUNT - 1 - suit
+}
+
+// Cards played in each slot of a trick block, as absolute card indices
+fn trick_cards(
+    inputs: &NeuralNetworkInput,
+    trick_start: usize,
+) -> [Option<usize>; CARDS_PER_TRICK] {
+    std::array::from_fn(|slot| {
+        let slot_start = trick_start + TRICK_CARD_OFFSET + slot * CARD_COUNT;
+        (0..CARD_COUNT).find(|&c| inputs[slot_start + c] != 0.0)
+    })
+}
+
+fn encode_suit_relative(inputs: &NeuralNetworkInput, frame: &SuitFrame) -> Vec<f64> {
+    let mut encoded = Vec::with_capacity(SUIT_RELATIVE_INPUT_COUNT);
+    // score, dealer and trick counts have no suits
+    encoded.extend_from_slice(&inputs[..StateIndex::UpcardSpadeNine as usize]);
+    for start in [
+        StateIndex::UpcardSpadeNine as usize,
+        StateIndex::HandSpadeNine as usize,
+    ] {
+        let mut cards = [0.0; CARD_COUNT];
+        for card in 0..CARD_COUNT {
+            cards[frame.card(card)] = inputs[start + card];
+        }
+        encoded.extend_from_slice(&cards);
+    }
+    encoded.extend_from_slice(
+        &inputs
+            [StateIndex::BidUpcardMyselfMake as usize..StateIndex::BidSuitMyselfMakeSpade as usize],
+    );
+    for position in 0..RELATIVE_POSITION_COUNT {
+        let start = StateIndex::BidSuitMyselfMakeSpade as usize + position * BID_SUIT_SLOT_COUNT;
+        let mut bids = [0.0; BID_SUIT_SLOT_COUNT];
+        for s in 0..SUIT_COUNT {
+            bids[frame.relative[s]] = inputs[start + s];
+            bids[SUIT_COUNT + frame.relative[s]] = inputs[start + SUIT_COUNT + s];
+        }
+        bids[2 * SUIT_COUNT] = inputs[start + 2 * SUIT_COUNT];
+        encoded.extend_from_slice(&bids);
+    }
+    let trump_known =
+        (0..SUIT_COUNT).any(|s| inputs[StateIndex::TrumpSuitSpade as usize + s] != 0.0);
+    encoded.push(if trump_known { 1.0 } else { 0.0 });
+
+    let mut played = [0.0; CARD_COUNT];
+    let mut voids = [0.0; (RELATIVE_POSITION_COUNT - 1) * SUIT_COUNT];
+    let mut current_trick = None;
+    for trick_start in (CONTEXT_COUNT..StateIndex::COUNT).step_by(TRICK_SLOT_COUNT) {
+        let lead = match (0..TRICK_LEAD_COUNT).find(|&p| inputs[trick_start + p] != 0.0) {
+            Some(lead) => lead,
+            None => break,
+        };
+        let lead_suit =
+            (0..SUIT_COUNT).find(|&s| inputs[trick_start + TRICK_LEAD_COUNT + s] != 0.0);
+        let cards = trick_cards(inputs, trick_start);
+        for (slot, card) in cards.iter().enumerate() {
+            if let Some(card) = *card {
+                played[frame.card(card)] = 1.0;
+                // following is by printed suit, so anyone who didn't is out of the lead suit
+                let position = (lead + slot) % RELATIVE_POSITION_COUNT;
+                match lead_suit {
+                    Some(lead_suit) if position != 0 && card / RANK_COUNT != lead_suit => {
+                        voids[(position - 1) * SUIT_COUNT + frame.relative[lead_suit]] = 1.0;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        current_trick = Some((lead, lead_suit, cards));
+    }
+    encoded.extend_from_slice(&played);
+
+    let mut leads = [0.0; TRICK_LEAD_COUNT];
+    let mut lead_suits = [0.0; SUIT_COUNT];
+    let mut trick = [0.0; CARD_COUNT];
+    let mut winning_card = [0.0; CARD_COUNT];
+    let mut winning_position = [0.0; RELATIVE_POSITION_COUNT];
+    if let Some((lead, lead_suit, cards)) = current_trick {
+        leads[lead] = 1.0;
+        if let Some(lead_suit) = lead_suit {
+            lead_suits[frame.relative[lead_suit]] = 1.0;
+        }
+        // trump beats everything and only cards of the lead suit can win otherwise, with lower
+        // relative indices being stronger in both
+        let mut winner: Option<(usize, usize, usize)> = None;
+        for (slot, card) in cards.iter().enumerate() {
+            if let Some(card) = *card {
+                let relative = frame.card(card);
+                trick[relative] = 1.0;
+                let strength = if relative < TRUMP_CARD_COUNT {
+                    relative
+                } else if Some(card / RANK_COUNT) == lead_suit {
+                    CARD_COUNT + relative
+                } else {
+                    continue;
+                };
+                if winner.is_none_or(|(best, _, _)| strength < best) {
+                    winner = Some((strength, relative, (lead + slot) % RELATIVE_POSITION_COUNT));
+                }
+            }
+        }
+        if let Some((_, relative, position)) = winner {
+            winning_card[relative] = 1.0;
+            winning_position[position] = 1.0;
+        }
+    }
+    encoded.extend_from_slice(&leads);
+    encoded.extend_from_slice(&lead_suits);
+    encoded.extend_from_slice(&trick);
+    encoded.extend_from_slice(&winning_card);
+    encoded.extend_from_slice(&winning_position);
+
+    // cards of each suit neither in hand nor played yet
+    let mut remaining = [0.0; SUIT_COUNT];
+    for card in 0..CARD_COUNT {
+        if inputs[StateIndex::HandSpadeNine as usize + card] == 0.0
+            && played[frame.card(card)] == 0.0
+        {
+            remaining[frame.relative[card / RANK_COUNT]] += 1.0 / RANK_COUNT as f64;
+        }
+    }
+    encoded.extend_from_slice(&remaining);
+    encoded.extend_from_slice(&voids);
+    encoded
+}
+
+// The context plus only the trick history that appeared since the previous inputs, as cards by
+// their order in the trick and who led, leaving the rest of the history to a recurrent network
+pub fn encode_events(
+    inputs: &NeuralNetworkInput,
+    previous: Option<&NeuralNetworkInput>,
+) -> Vec<f64> {
+    let mut events = vec![0.0; EVENT_INPUT_COUNT];
+    events[..CONTEXT_COUNT].copy_from_slice(&inputs[..CONTEXT_COUNT]);
+    let is_new = |i: usize| inputs[i] != 0.0 && previous.is_none_or(|p| p[i] == 0.0);
+    for trick_start in (CONTEXT_COUNT..StateIndex::COUNT).step_by(TRICK_SLOT_COUNT) {
+        for lead in 0..TRICK_LEAD_COUNT {
+            if is_new(trick_start + lead) {
+                events[CONTEXT_COUNT + CARDS_PER_TRICK * CARD_COUNT + lead] = 1.0;
+            }
+        }
+        for slot in 0..CARDS_PER_TRICK * CARD_COUNT {
+            if is_new(trick_start + TRICK_CARD_OFFSET + slot) {
+                events[CONTEXT_COUNT + slot] = 1.0;
+            }
+        }
+    }
+    events
+}
@@ -0,0 +1,214 @@
+use std::str::FromStr;
+
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::euchre::{
+    bots::Bot,
+    enums::Team,
+    game::{play_euchre, play_euchre_with_summaries},
+    statistics::{GameStatistics, PlayStatistics},
+};
+
+use super::neural_network::NeuralNetwork;
+use super::policy::ActionPolicy;
+use super::training::DecisionRecord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartnerMode {
+    // each organism sits across from a copy of itself
+    SelfPartner,
+    // teams are two different organisms, reshuffled every round
+    RotatingPartners,
+}
+
+// North/south team against east/west team, as indices into the population
+pub type Matchup = ([usize; 2], [usize; 2]);
+
+pub struct MatchResult {
+    pub north_south_won: bool,
+    // games won by north/south and east/west
+    pub games_won: [usize; 2],
+    // the first decision with NaN or infinite outputs by each network, indexed like the teams
+    pub non_finite: [[Option<DecisionRecord>; 2]; 2],
+    // how each network bid and led over the match, indexed like the teams
+    pub statistics: [[PlayStatistics; 2]; 2],
+}
+
+// Best of games_per_match
+pub fn play_match(
+    north_south: [&NeuralNetwork; 2],
+    east_west: [&NeuralNetwork; 2],
+    games_per_match: usize,
+    policy: ActionPolicy,
+) -> MatchResult {
+    let (mut north_south_wins, mut east_west_wins) = (0, 0);
+    let games_to_win = games_per_match / 2 + 1;
+    let mut non_finite: [[Option<DecisionRecord>; 2]; 2] = Default::default();
+    let mut statistics = GameStatistics::default();
+    let mut rng = rand::thread_rng();
+    for _ in 0..games_per_match {
+        let seats = [
+            north_south.map(|nn| nn.seat_with_policy(policy)),
+            east_west.map(|nn| nn.seat_with_policy(policy)),
+        ];
+        match play_euchre_with_summaries(
+            &seats[0][0],
+            &seats[1][0],
+            &seats[0][1],
+            &seats[1][1],
+            &mut rng,
+            &mut |summary| statistics.record(summary),
+        ) {
+            Team::NorthSouth => north_south_wins += 1,
+            Team::EastWest => east_west_wins += 1,
+        }
+        for (team, team_seats) in seats.iter().enumerate() {
+            for (i, seat) in team_seats.iter().enumerate() {
+                if non_finite[team][i].is_none() {
+                    non_finite[team][i] = seat.non_finite_decision();
+                }
+            }
+        }
+        if north_south_wins == games_to_win || east_west_wins == games_to_win {
+            return MatchResult {
+                north_south_won: north_south_wins == games_to_win,
+                games_won: [north_south_wins, east_west_wins],
+                non_finite,
+                // north and south are seats 0 and 2, east and west 1 and 3
+                statistics: [0, 1].map(|team| [0, 2].map(|i| statistics.seats[team + i].clone())),
+            };
+        }
+    }
+    panic!("couldn't finish match")
+}
+
+// Fraction of games a self partnered network wins against a self partnered bot, sitting
+// north/south in even games and east/west in odd ones
+pub fn baseline_win_rate(nn: &NeuralNetwork, bot: Bot, games: usize, policy: ActionPolicy) -> f64 {
+    play_baseline(nn, bot, games, policy).win_rate
+}
+
+pub struct BaselineResult {
+    pub win_rate: f64,
+    // how the network bid and led over the games
+    pub statistics: PlayStatistics,
+    // the first decision with NaN or infinite outputs in either of the network's seats
+    pub non_finite: Option<DecisionRecord>,
+}
+
+// Like baseline_win_rate, along with how the network bid and led over the games
+pub fn play_baseline(
+    nn: &NeuralNetwork,
+    bot: Bot,
+    games: usize,
+    policy: ActionPolicy,
+) -> BaselineResult {
+    let mut wins = 0;
+    let mut played = PlayStatistics::default();
+    let mut non_finite = None;
+    let mut rng = rand::thread_rng();
+    for game in 0..games {
+        let (seat, partner) = (nn.seat_with_policy(policy), nn.seat_with_policy(policy));
+        let mut statistics = GameStatistics::default();
+        let team = game % 2;
+        let winner = if team == 0 {
+            play_euchre_with_summaries(&seat, &bot, &partner, &bot, &mut rng, &mut |summary| {
+                statistics.record(summary)
+            })
+        } else {
+            play_euchre_with_summaries(&bot, &seat, &bot, &partner, &mut rng, &mut |summary| {
+                statistics.record(summary)
+            })
+        };
+        if let (0, Team::NorthSouth) | (1, Team::EastWest) = (team, winner) {
+            wins += 1;
+        }
+        played.merge(&statistics.seats[team]);
+        played.merge(&statistics.seats[team + 2]);
+        if non_finite.is_none() {
+            non_finite = seat
+                .non_finite_decision()
+                .or_else(|| partner.non_finite_decision());
+        }
+    }
+    BaselineResult {
+        win_rate: wins as f64 / games as f64,
+        statistics: played,
+        non_finite,
+    }
+}
+
+impl FromStr for PartnerMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "self_partner" => Ok(PartnerMode::SelfPartner),
+            "rotating_partners" => Ok(PartnerMode::RotatingPartners),
+            _ => Err(format!("unknown partner mode {}", s)),
+        }
+    }
+}
+
+impl PartnerMode {
+    pub fn matches_per_round(&self, population_size: usize) -> usize {
+        match self {
+            PartnerMode::SelfPartner => population_size / 2,
+            PartnerMode::RotatingPartners => population_size / 4,
+        }
+    }
+
+    // Every organism plays in exactly one matchup per round
+    pub fn schedule_matchups<R: Rng>(
+        &self,
+        population_indices: &mut [usize],
+        rng: &mut R,
+    ) -> Vec<Matchup> {
+        population_indices.shuffle(rng);
+        match self {
+            PartnerMode::SelfPartner => population_indices
+                .chunks_exact(2)
+                .map(|pair| ([pair[0], pair[0]], [pair[1], pair[1]]))
+                .collect(),
+            PartnerMode::RotatingPartners => population_indices
+                .chunks_exact(4)
+                .map(|group| ([group[0], group[1]], [group[2], group[3]]))
+                .collect(),
+        }
+    }
+}
+
+// Win rate of the agent with each partner in turn, against opponents drawn from the other
+// partners, alternating which side of the table the agent's team sits on
+pub fn partner_robustness<R: Rng>(
+    agent: &NeuralNetwork,
+    partners: &[NeuralNetwork],
+    games_per_partner: usize,
+    rng: &mut R,
+) -> Vec<f64> {
+    let mut win_rates = Vec::with_capacity(partners.len());
+    for (i, partner) in partners.iter().enumerate() {
+        let others: Vec<usize> = (0..partners.len()).filter(|&j| j != i).collect();
+        let mut wins = 0;
+        for game in 0..games_per_partner {
+            let opponents: Vec<&NeuralNetwork> = others
+                .choose_multiple(rng, 2)
+                .map(|&j| &partners[j])
+                .collect();
+            let (left, right) = (opponents[0], opponents[opponents.len() - 1]);
+            let (agent, partner, left, right) =
+                (agent.seat(), partner.seat(), left.seat(), right.seat());
+            if game % 2 == 0 {
+                if let Team::NorthSouth = play_euchre(&agent, &left, &partner, &right) {
+                    wins += 1;
+                }
+            } else if let Team::EastWest = play_euchre(&left, &agent, &right, &partner) {
+                wins += 1;
+            }
+        }
+        win_rates.push(wins as f64 / games_per_partner as f64);
+    }
+    win_rates
+}
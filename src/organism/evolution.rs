@@ -1,94 +1,94 @@
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::{
     error::Error,
     fs,
     sync::{Arc, Mutex},
+    time::Instant,
 };
 
-use crate::euchre::{enums::Team, game::play_euchre};
-
-use super::neural_network::NeuralNetwork;
-use rand::{seq::SliceRandom, thread_rng};
+use super::config::EvolutionConfig;
+use super::curriculum::{Curriculum, TaskResult};
+use super::diversity::{population_diversity, probe_positions, PROBE_SEED};
+use super::evaluation::{baseline_win_rate, play_match, MatchResult};
+use super::metrics::{GenerationMetrics, MetricsLog};
+use super::neural_network::{weight_spread, NeuralNetwork};
+use super::novelty::{descriptor, Descriptor, SearchArchive};
+use super::pareto::{non_dominated_fronts, rank_by_pareto};
+use super::selection::{rank_by_fitness, SelectionScheme};
+use super::tournament::{run_tournament, TournamentResult};
+use super::training::{append_records, DecisionRecord};
+use crate::euchre::statistics::PlayStatistics;
+use rand::{thread_rng, Rng};
 use rayon::prelude::*;
+use serde_json::json;
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct Organism {
     pub brain: Option<NeuralNetwork>,
     pub lifetime: usize,
     pub generation: usize,
 }
 
-// must be a multiple of 4
-
-fn play_match(organism1: &Organism, organism2: &Organism) -> bool {
-    let (mut organism1_wins, mut orgaism2_wins) = (0, 0);
-    for _ in 0..3 {
-        match play_euchre(
-            &organism1.brain.unwrap(),
-            &organism2.brain.unwrap(),
-            &organism1.brain.unwrap(),
-            &organism2.brain.unwrap(),
-        ) {
-            Team::NorthSouth => organism1_wins += 1,
-            Team::EastWest => orgaism2_wins += 1,
-        }
-        match (organism1_wins, orgaism2_wins) {
-            (2, _) => return true,
-            (_, 2) => return false,
-            _ => (),
-        }
-    }
-    panic!("couldn't finish match")
-}
-
-pub fn evolve<const POPULATION_SIZE: usize, const BREEDING_POOL_SIZE: usize>(
-    generations: usize,
+pub fn evolve(
+    config: &EvolutionConfig,
     out_dir: String,
-    thread_count: usize,
-    stack_size: usize,
     starting_population_dir: Option<String>,
     no_gen_save: bool,
 ) -> Result<Organism, Box<dyn Error>> {
+    let population_size = config.population_size;
+    let generations = config.generations;
+    let survivor_count = config.survivor_count();
+    let breeder_count = config.breeder_count();
+    let child_count_total = population_size - survivor_count;
+    let partner_mode = config.evaluation.partner_mode;
+    let match_count_total = partner_mode.matches_per_round(population_size);
+    let games_per_match = config.evaluation.games_per_match;
+    let policy = config.evaluation.policy;
+    let rounds = config.evaluation.rounds;
+
+    fs::create_dir_all(&out_dir)?;
+    config.save_to_file(format!("{}/config.json", out_dir).as_str())?;
+
     // Initialize
     println!("Initializing");
-    let mut organisms: [Organism; POPULATION_SIZE] = [Organism {
-        brain: None,
-        lifetime: 0,
-        generation: 0,
-    }; POPULATION_SIZE];
-    let mut children: [Organism; BREEDING_POOL_SIZE] = [Organism {
-        brain: None,
-        lifetime: 0,
-        generation: 0,
-    }; BREEDING_POOL_SIZE];
-    let mut breeder_indices: [usize; BREEDING_POOL_SIZE] = [0; BREEDING_POOL_SIZE];
+    let mut organisms: Vec<Organism> = vec![
+        Organism {
+            brain: None,
+            lifetime: 0,
+            generation: 0,
+        };
+        population_size
+    ];
+    let mut children: Vec<Organism> = vec![
+        Organism {
+            brain: None,
+            lifetime: 0,
+            generation: 0,
+        };
+        child_count_total
+    ];
     let mut rng = thread_rng();
 
     let pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(thread_count)
-        .stack_size(stack_size)
+        .num_threads(config.thread_count)
         .build()
         .unwrap();
 
     let mut file_names: Vec<String> = Vec::new();
     let mut loading = false;
     let mut verbage = "Spawning";
-    match starting_population_dir {
-        Some(dir) => {
-            let entries = fs::read_dir(dir);
-            file_names = entries?
-                .filter_map(|entry| {
-                    entry
-                        .ok()
-                        .and_then(|e| e.path().to_str().map(|s| s.to_owned()))
-                })
-                .collect::<Vec<String>>();
-            loading = true;
-            verbage = "Loading";
-        }
-        None => (),
+    if let Some(dir) = starting_population_dir {
+        let entries = fs::read_dir(dir);
+        file_names = entries?
+            .filter_map(|entry| {
+                entry
+                    .ok()
+                    .and_then(|e| e.path().to_str().map(|s| s.to_owned()))
+            })
+            .collect::<Vec<String>>();
+        loading = true;
+        verbage = "Loading";
     }
-    if loading && file_names.len() != POPULATION_SIZE {
+    if loading && file_names.len() != population_size {
         panic!("Invalid generation size per starting pool");
     }
 
@@ -108,70 +108,322 @@ pub fn evolve<const POPULATION_SIZE: usize, const BREEDING_POOL_SIZE: usize>(
                 }
                 println!(
                     "{} Organisms - {}/{}",
-                    verbage, organism_count_val, POPULATION_SIZE
+                    verbage, organism_count_val, population_size
                 );
-                let mut nn = NeuralNetwork::new();
+                let mut nn = NeuralNetwork::from_config(&config.network);
                 if loading {
                     nn.load_from_file(file_names[organism_count_val - 1].as_str())
                         .unwrap();
                 } else {
-                    nn.init();
+                    nn.init(&config.init);
                 }
-                (*organism).brain = Some(nn);
+                organism.brain = Some(nn);
             });
     });
 
-    let mut population_indices = [0; POPULATION_SIZE];
-    for i in 0..POPULATION_SIZE {
-        population_indices[i] = i;
+    // crossover needs every organism to have the same shape
+    let network_config = organisms[0].brain.as_ref().unwrap().config();
+    if let Some(organism) = organisms
+        .iter()
+        .find(|organism| organism.brain.as_ref().unwrap().config() != network_config)
+    {
+        return Err(format!(
+            "starting population mixes {:?} and {:?}",
+            network_config,
+            organism.brain.as_ref().unwrap().config()
+        )
+        .into());
     }
 
+    let mut population_indices: Vec<usize> = (0..population_size).collect();
+    let mut metrics_log = MetricsLog::create(&out_dir, config.metrics.format)?;
+    let probes = probe_positions(config.metrics.diversity_probes, PROBE_SEED);
+    let mut archive = SearchArchive::new(config.search);
+    let mut curriculum_level = 0;
+
     // Run Generations
     println!("Generations");
     let mut generation = 0;
     while generation < generations {
         generation += 1;
-        population_indices.shuffle(&mut rand::thread_rng());
 
         println!("Generation {} - Playing Games", generation);
-        let match_count = Arc::new(Mutex::new(0));
-        pool.install(|| {
-            breeder_indices
-                .par_iter_mut()
-                .enumerate()
-                .for_each(|(i, breed_index)| {
-                    let match_count_val;
+        let play_start = Instant::now();
+        let mut fitness: Vec<f64> = vec![0.0; population_size];
+        let mut reported = vec![false; population_size];
+        let mut behaviour = vec![PlayStatistics::default(); population_size];
+        if config.curriculum.plays_matches(curriculum_level) {
+            let match_count = Arc::new(Mutex::new(0));
+            for _ in 0..rounds {
+                let matchups = partner_mode.schedule_matchups(&mut population_indices, &mut rng);
+                let match_results: Vec<MatchResult> = pool.install(|| {
+                    matchups
+                        .par_iter()
+                        .map(|(north_south, east_west)| {
+                            let match_count_val;
+                            {
+                                let mut match_count_guard = match_count.lock().unwrap();
+                                *match_count_guard += 1;
+                                match_count_val = *match_count_guard;
+                            }
+                            println!(
+                                "Generation {} - Match {}/{}",
+                                generation,
+                                match_count_val,
+                                match_count_total * rounds
+                            );
+                            play_match(
+                                north_south.map(|i| organisms[i].brain.as_ref().unwrap()),
+                                east_west.map(|i| organisms[i].brain.as_ref().unwrap()),
+                                games_per_match,
+                                policy,
+                            )
+                        })
+                        .collect()
+                });
+                for ((north_south, east_west), result) in matchups.iter().zip(match_results.iter())
+                {
+                    for (team, records) in [north_south, east_west].iter().zip(&result.non_finite) {
+                        for (&i, record) in team.iter().zip(records) {
+                            if let Some(record) = record {
+                                if !reported[i] {
+                                    reported[i] = true;
+                                    report_non_finite(
+                                        &out_dir,
+                                        generation,
+                                        i,
+                                        organisms[i].brain.as_ref().unwrap(),
+                                        record,
+                                    )?;
+                                }
+                            }
+                        }
+                    }
+                    for (team, statistics) in
+                        [north_south, east_west].iter().zip(&result.statistics)
                     {
-                        let mut match_count_guard = match_count.lock().unwrap();
-                        *match_count_guard += 1;
-                        match_count_val = *match_count_guard;
+                        for (&i, statistics) in team.iter().zip(statistics) {
+                            behaviour[i].merge(statistics);
+                        }
                     }
-                    println!(
-                        "Generation {} - Match {}/{}",
-                        generation, match_count_val, BREEDING_POOL_SIZE
-                    );
-                    let index = i * 2;
-                    *breed_index = if play_match(
-                        &organisms[population_indices[index]],
-                        &organisms[population_indices[index + 1]],
-                    ) {
-                        population_indices[index]
+                    let winners = if result.north_south_won {
+                        north_south
                     } else {
-                        population_indices[index + 1]
+                        east_west
                     };
-                });
-        });
+                    // a self partnered team is one organism sitting twice
+                    fitness[winners[0]] += 1.0 / rounds as f64;
+                    if winners[1] != winners[0] {
+                        fitness[winners[1]] += 1.0 / rounds as f64;
+                    }
+                }
+            }
+        } else {
+            let task = config.curriculum.task(curriculum_level);
+            let deal_seeds = config.curriculum.deal_seeds(&mut rng);
+            let task_count = Arc::new(Mutex::new(0));
+            let results: Vec<TaskResult> = pool.install(|| {
+                organisms
+                    .par_iter()
+                    .map(|organism| {
+                        let task_count_val;
+                        {
+                            let mut task_count_guard = task_count.lock().unwrap();
+                            *task_count_guard += 1;
+                            task_count_val = *task_count_guard;
+                        }
+                        println!(
+                            "Generation {} - Curriculum {} - Organism {}/{}",
+                            generation, task, task_count_val, population_size
+                        );
+                        config.curriculum.evaluate(
+                            curriculum_level,
+                            organism.brain.as_ref().unwrap(),
+                            &deal_seeds,
+                            policy,
+                        )
+                    })
+                    .collect()
+            });
+            for (i, result) in results.into_iter().enumerate() {
+                if let Some(record) = &result.non_finite {
+                    report_non_finite(
+                        &out_dir,
+                        generation,
+                        i,
+                        organisms[i].brain.as_ref().unwrap(),
+                        record,
+                    )?;
+                }
+                fitness[i] = result.fitness;
+                behaviour[i] = result.statistics;
+            }
+        }
 
-        println!("Generation {} - Breeding Children", generation);
-        // Select parents
-        let mut parent_matchings: [(usize, usize); BREEDING_POOL_SIZE] =
-            [(0, 0); BREEDING_POOL_SIZE];
-        for i in 0..BREEDING_POOL_SIZE {
-            let parent_indexes =
-                rand::seq::index::sample(&mut rng, BREEDING_POOL_SIZE, 2).into_vec();
-            parent_matchings[i] = (parent_indexes[0], parent_indexes[1]);
+        let descriptors: Vec<Descriptor> = behaviour.iter().map(descriptor).collect();
+        let mut metrics = population_metrics(generation, &organisms, &fitness, &descriptors);
+        metrics.play_seconds = play_start.elapsed().as_secs_f64();
+        if config.curriculum != Curriculum::FullGame {
+            metrics.task = Some(config.curriculum.task(curriculum_level));
+            let next_level = config
+                .curriculum
+                .next_level(curriculum_level, metrics.mean_fitness);
+            if next_level != curriculum_level {
+                curriculum_level = next_level;
+                println!(
+                    "Generation {} - Curriculum - Mean fitness {} reached, moving on to {}",
+                    generation,
+                    metrics.mean_fitness,
+                    config.curriculum.task(curriculum_level)
+                );
+            }
+        }
+
+        let baseline_start = Instant::now();
+        for &bot in config.metrics.baselines.iter() {
+            println!(
+                "Generation {} - Playing Baseline {}",
+                generation,
+                bot.name()
+            );
+            let win_rates: Vec<f64> = pool.install(|| {
+                organisms
+                    .par_iter()
+                    .map(|organism| {
+                        baseline_win_rate(
+                            organism.brain.as_ref().unwrap(),
+                            bot,
+                            config.metrics.baseline_games,
+                            policy,
+                        )
+                    })
+                    .collect()
+            });
+            let win_rate = win_rates.iter().sum::<f64>() / population_size as f64;
+            println!(
+                "Generation {} - Baseline {} - Win Rate {}",
+                generation,
+                bot.name(),
+                win_rate
+            );
+            metrics.baseline_win_rates.push((bot, win_rate));
+        }
+        metrics.baseline_seconds = baseline_start.elapsed().as_secs_f64();
+
+        let networks: Vec<&NeuralNetwork> = organisms
+            .iter()
+            .map(|organism| organism.brain.as_ref().unwrap())
+            .collect();
+        let diversity_start = Instant::now();
+        if !probes.is_empty() {
+            let diversity = pool.install(|| population_diversity(&networks, &probes))?;
+            println!(
+                "Generation {} - Diversity - Weight: {}, Connection: {}, Activation: {}, Behaviour: {}, Distinct Behaviours: {}",
+                generation,
+                diversity.weight_distance,
+                diversity.connection_distance,
+                diversity.activation_distance,
+                diversity.behavioural_distance,
+                diversity.distinct_behaviours
+            );
+            if diversity.behavioural_distance < config.metrics.convergence_threshold {
+                println!(
+                    "Generation {} - Organisms disagree on {} of probe positions, below {}, the population may have converged",
+                    generation, diversity.behavioural_distance, config.metrics.convergence_threshold
+                );
+            }
+            metrics.diversity = Some(diversity);
         }
-        breeder_indices.sort();
+        metrics.diversity_seconds = diversity_start.elapsed().as_secs_f64();
+
+        // what survivors and parents are selected on, fitness blended with novelty in novelty search
+        let selection_fitness = archive.update(
+            config.search,
+            generation,
+            &networks,
+            &fitness,
+            &descriptors,
+            &mut rng,
+        );
+        if let Some(size) = archive.size() {
+            println!("Generation {} - Archive Size {}", generation, size);
+            metrics.archive_size = Some(size);
+        }
+
+        // Select survivors and parents
+        let breed_start = Instant::now();
+        let ranking = match config.selection.scheme {
+            SelectionScheme::Nsga2 { objective } => {
+                // the fitness played this generation, a win rate or the curriculum task's
+                // fitness, against the network complexity objective
+                let objectives: Vec<Vec<f64>> = organisms
+                    .iter()
+                    .zip(fitness.iter())
+                    .map(|(organism, fitness)| {
+                        vec![*fitness, objective.value(organism.brain.as_ref().unwrap())]
+                    })
+                    .collect();
+                let front = &non_dominated_fronts(&objectives)[0];
+                println!(
+                    "Generation {} - Pareto Front - {} Organisms",
+                    generation,
+                    front.len()
+                );
+                if generation == generations {
+                    save_pareto_front(&out_dir, &organisms, &fitness, front)?;
+                }
+                rank_by_pareto(&objectives, &mut rng)
+            }
+            _ => rank_by_fitness(&selection_fitness, &mut rng),
+        };
+        let mut survivor_indices: Vec<usize> = ranking[..survivor_count].to_vec();
+        survivor_indices.sort();
+        let (parents, parent_matchings) = match archive.elites() {
+            Some(elites) => {
+                let matchings = (0..child_count_total)
+                    .map(|_| {
+                        (
+                            rng.gen_range(0..elites.len()),
+                            rng.gen_range(0..elites.len()),
+                        )
+                    })
+                    .collect();
+                (elites, matchings)
+            }
+            None => (
+                networks,
+                config.selection.scheme.select_parents(
+                    &selection_fitness,
+                    &ranking,
+                    breeder_count,
+                    child_count_total,
+                    &mut rng,
+                ),
+            ),
+        };
+
+        let mutation = config.mutation.parameters(generation, generations);
+        println!(
+            "Generation {} - Mutation Rates - Bias: {}, Weight: {}, Connection: {}, Activation: {}",
+            generation,
+            mutation.bias_rate,
+            mutation.weight_rate,
+            mutation.connection_rate,
+            mutation.activation_rate
+        );
+        if mutation.self_adaptive {
+            println!(
+                "Generation {} - Mutation Magnitudes - Self-Adaptive",
+                generation
+            );
+        } else {
+            println!(
+                "Generation {} - Mutation Magnitudes - Bias: {}, Weight: {}",
+                generation, mutation.bias_magnitude, mutation.weight_magnitude
+            );
+        }
+
+        println!("Generation {} - Breeding Children", generation);
         let child_count = Arc::new(Mutex::new(0));
 
         // Do breeding
@@ -185,23 +437,13 @@ pub fn evolve<const POPULATION_SIZE: usize, const BREEDING_POOL_SIZE: usize>(
                 }
                 println!(
                     "Generation {} - Breeding Child {}/{}",
-                    generation, child_count_val, BREEDING_POOL_SIZE
+                    generation, child_count_val, child_count_total
                 );
                 let (j, k) = parent_matchings[i];
                 *child = Organism {
-                    brain: Some(
-                        organisms[breeder_indices[j]]
-                            .brain
-                            .as_ref()
-                            .unwrap()
-                            .crossover(
-                                organisms[breeder_indices[k]].brain.as_ref().unwrap(),
-                                0.01,
-                                0.1,
-                            ),
-                    ),
+                    brain: Some(parents[j].crossover(parents[k], &mutation).unwrap()),
                     lifetime: 0,
-                    generation: generation,
+                    generation,
                 };
             });
         });
@@ -209,16 +451,19 @@ pub fn evolve<const POPULATION_SIZE: usize, const BREEDING_POOL_SIZE: usize>(
         // Write children back to organisms
         let mut check_cursor: usize = 0;
         let mut child_cursor = 0;
-        for i in 0..POPULATION_SIZE {
-            if check_cursor < BREEDING_POOL_SIZE && breeder_indices[check_cursor] == i {
-                organisms[i].lifetime += 1;
+        for (i, organism) in organisms.iter_mut().enumerate().take(population_size) {
+            if check_cursor < survivor_count && survivor_indices[check_cursor] == i {
+                organism.lifetime += 1;
                 check_cursor += 1;
             } else {
-                organisms[i] = children[child_cursor];
+                std::mem::swap(organism, &mut children[child_cursor]);
                 child_cursor += 1;
             }
         }
 
+        metrics.breed_seconds = breed_start.elapsed().as_secs_f64();
+
+        let save_start = Instant::now();
         if !no_gen_save || generation == generations {
             fs::create_dir_all(format!("{}/gen_{}", out_dir, generation))?;
             println!("Generation {} - Saving Generation", generation);
@@ -237,71 +482,175 @@ pub fn evolve<const POPULATION_SIZE: usize, const BREEDING_POOL_SIZE: usize>(
                 });
             });
         }
+        metrics.save_seconds = save_start.elapsed().as_secs_f64();
+        metrics_log.append(&metrics)?;
     }
 
-    // Round Robin for Champ
+    archive.save(&out_dir)?;
+
     println!("Tournament");
-    let total_rounds = f64::log2(POPULATION_SIZE as f64) as usize;
+    let networks: Vec<&NeuralNetwork> = organisms
+        .iter()
+        .map(|organism| organism.brain.as_ref().unwrap())
+        .collect();
+    let result = run_tournament(
+        config.tournament.format,
+        &networks,
+        config.tournament.games_per_match,
+        policy,
+        &pool,
+    );
+    save_tournament(&out_dir, config, &organisms, &result)?;
+    let champion = &organisms[result.standings[0].index];
+    champion
+        .brain
+        .as_ref()
+        .unwrap()
+        .save_to_file(format!("{}/champion.bin", out_dir).as_str())?;
+    Ok(champion.clone())
+}
 
-    let mut alive = Vec::with_capacity(POPULATION_SIZE);
-    for _ in 0..POPULATION_SIZE {
-        alive.push(AtomicBool::new(true));
+// Metrics of the population as it was evaluated, before survivors are picked
+fn population_metrics(
+    generation: usize,
+    organisms: &[Organism],
+    fitness: &[f64],
+    descriptors: &[Descriptor],
+) -> GenerationMetrics {
+    let count = organisms.len() as f64;
+    let networks: Vec<&NeuralNetwork> = organisms
+        .iter()
+        .map(|organism| organism.brain.as_ref().unwrap())
+        .collect();
+    let oldest = organisms.iter().map(|o| o.lifetime).max().unwrap_or(0);
+    let mut lifetime_counts = vec![0; oldest + 1];
+    for organism in organisms.iter() {
+        lifetime_counts[organism.lifetime] += 1;
     }
-    let alive = Arc::new(Mutex::new(alive));
-
-    for round_number in 1..total_rounds + 1 {
-        println!("Tournament Round {}/{}", round_number, total_rounds);
-        let mut matchups: Vec<(usize, usize)> = Vec::with_capacity(POPULATION_SIZE / 2);
-        let mut next_matchup: Vec<usize> = Vec::with_capacity(2);
-        let alive_guard = alive.lock().unwrap();
-        for (i, alive) in alive_guard.iter().enumerate() {
-            if alive.load(Ordering::SeqCst) {
-                next_matchup.push(i);
-                if next_matchup.len() == 2 {
-                    matchups.push((next_matchup[0], next_matchup[1]));
-                    next_matchup.clear();
-                }
-            }
+    let mut activation_rates = [0.0; 3];
+    for nn in networks.iter() {
+        for (sum, rate) in activation_rates.iter_mut().zip(nn.activation_rates()) {
+            *sum += rate / count;
         }
-        drop(alive_guard);
-        let match_count = Arc::new(Mutex::new(0));
-
-        pool.install(|| {
-            matchups.par_iter().for_each(|(i, j)| {
-                let match_count_val;
-                {
-                    let mut match_count_guard = match_count.lock().unwrap();
-                    *match_count_guard += 1;
-                    match_count_val = *match_count_guard;
-                }
-                println!(
-                    "Trounament Round {}/{} - Match {}/{}",
-                    round_number,
-                    total_rounds,
-                    match_count_val,
-                    matchups.len()
-                );
-                let loser_index = match play_match(&organisms[*i], &organisms[*j]) {
-                    true => *j,
-                    false => *i,
-                };
-                let alive_guard = alive.lock().unwrap();
-                alive_guard[loser_index].store(false, Ordering::SeqCst);
-            });
-        });
     }
+    let mut mean_descriptor = [0.0; 3];
+    for descriptor in descriptors.iter() {
+        for (sum, value) in mean_descriptor.iter_mut().zip(descriptor) {
+            *sum += value / count;
+        }
+    }
+    GenerationMetrics {
+        generation,
+        lifetime_counts,
+        mean_lifetime: organisms.iter().map(|o| o.lifetime as f64).sum::<f64>() / count,
+        mean_fitness: fitness.iter().sum::<f64>() / count,
+        best_fitness: fitness.iter().copied().fold(0.0, f64::max),
+        mean_connection_rate: networks.iter().map(|nn| nn.connection_rate()).sum::<f64>() / count,
+        activation_rates,
+        weight_spread: weight_spread(&networks),
+        mean_descriptor,
+        ..Default::default()
+    }
+}
 
-    fs::create_dir_all(format!("{}", out_dir))?;
-    let alive_guard = alive.lock().unwrap();
-    for (i, alive) in alive_guard.iter().enumerate() {
-        if alive.load(Ordering::SeqCst) {
-            organisms[i]
-                .brain
-                .unwrap()
-                .save_to_file(format!("{}/champion.bin", out_dir).as_str())?;
-            return Ok(organisms[i]);
+// Writes every match and the standings to tournament/results.json, and the top_k organisms to
+// tournament/top/ prefixed with their rank
+fn save_tournament(
+    out_dir: &str,
+    config: &EvolutionConfig,
+    organisms: &[Organism],
+    result: &TournamentResult,
+) -> Result<(), Box<dyn Error>> {
+    println!("Saving Tournament");
+    let top_dir = format!("{}/tournament/top", out_dir);
+    fs::create_dir_all(&top_dir)?;
+    let mut standings = Vec::with_capacity(result.standings.len());
+    for (rank, standing) in result.standings.iter().enumerate() {
+        let organism = &organisms[standing.index];
+        let mut value = serde_json::to_value(standing)?;
+        value["rank"] = json!(rank + 1);
+        value["lifetime"] = json!(organism.lifetime);
+        value["generation"] = json!(organism.generation);
+        standings.push(value);
+        if rank < config.tournament.top_k {
+            organism.brain.as_ref().unwrap().save_to_file(
+                format!(
+                    "{}/rank({})-index({})-lifetime({})-generation({}).bin",
+                    top_dir,
+                    rank + 1,
+                    standing.index,
+                    organism.lifetime,
+                    organism.generation
+                )
+                .as_str(),
+            )?;
         }
     }
-    drop(alive_guard);
-    panic!("no champion found");
+    let results = json!({
+        "format": config.tournament.format,
+        "games_per_match": config.tournament.games_per_match,
+        "matches": result.matches,
+        "standings": standings,
+    });
+    fs::write(
+        format!("{}/tournament/results.json", out_dir),
+        serde_json::to_string_pretty(&results)?,
+    )?;
+    Ok(())
+}
+
+// Saves a network whose outputs went NaN or infinite next to the decision that showed it, so it
+// can be inspected without stopping the run
+fn report_non_finite(
+    out_dir: &str,
+    generation: usize,
+    index: usize,
+    nn: &NeuralNetwork,
+    record: &DecisionRecord,
+) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(format!("{}/non_finite", out_dir))?;
+    let name = format!(
+        "{}/non_finite/generation({})-index({})",
+        out_dir, generation, index
+    );
+    nn.save_to_file(&format!("{}.bin", name))?;
+    append_records(&format!("{}.jsonl", name), std::slice::from_ref(record))?;
+    println!(
+        "Generation {} - Organism {} produced NaN or infinite outputs, saved to {}.bin with the input in {}.jsonl",
+        generation, index, name, name
+    );
+    Ok(())
+}
+
+// Writes the networks on the Pareto front of the final evaluated generation along with a
+// front.csv of their objectives
+fn save_pareto_front(
+    out_dir: &str,
+    organisms: &[Organism],
+    fitness: &[f64],
+    front: &[usize],
+) -> Result<(), Box<dyn Error>> {
+    println!("Saving Pareto Front");
+    let pareto_dir = format!("{}/pareto", out_dir);
+    fs::create_dir_all(&pareto_dir)?;
+    let mut front = front.to_vec();
+    front.sort_by(|&a, &b| fitness[b].total_cmp(&fitness[a]));
+    let mut csv = String::from("file,fitness,connection_rate,connections\n");
+    for i in front {
+        let brain = organisms[i].brain.as_ref().unwrap();
+        let filename = format!(
+            "index({})-lifetime({})-generation({}).bin",
+            i, organisms[i].lifetime, organisms[i].generation
+        );
+        brain.save_to_file(format!("{}/{}", pareto_dir, filename).as_str())?;
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            filename,
+            fitness[i],
+            brain.connection_rate(),
+            brain.connection_count().0
+        ));
+    }
+    fs::write(format!("{}/front.csv", pareto_dir), csv)?;
+    Ok(())
 }
@@ -1,7 +1,5 @@
 use std::{collections::HashMap, io::Write};
 
-use strum::EnumCount;
-
 use crate::euchre::enums::{ActionIndex, StateIndex};
 
 use super::neural_network::{AvailableActions, NeuralNetworkInput};
@@ -18,7 +16,7 @@ pub fn get_player_action(
 
     println!("Options\n");
     for key in keys.iter() {
-        println!("{}: {:?}", key, options.get(&key).unwrap());
+        println!("{}: {:?}", key, options.get(key).unwrap());
     }
 
     loop {
@@ -63,22 +61,22 @@ fn describe_inputs(inputs: &NeuralNetworkInput) {
     print!("\x1B[2J\x1B[1;1H");
     std::io::stdout().flush().expect("Failed to flush stdout");
     println!("Information\n");
-    for i in 0..StateIndex::COUNT {
-        if inputs[i] == 1.0 {
+    for (i, &input) in inputs.iter().enumerate() {
+        if input == 1.0 {
             println!("{:?}", StateIndex::from_usize(i));
         }
     }
-    println!("");
+    println!();
 }
 
 fn get_choices(available_actions: &AvailableActions) -> HashMap<usize, ActionIndex> {
     let mut options: HashMap<usize, ActionIndex> = HashMap::new();
     let mut key: usize = 1;
-    for i in 0..available_actions.len() {
-        if available_actions[i] {
+    for (i, &available) in available_actions.iter().enumerate() {
+        if available {
             options.insert(key, ActionIndex::from_usize(i));
             key += 1;
         }
     }
-    return options;
+    options
 }
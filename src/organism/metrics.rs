@@ -0,0 +1,200 @@
+use std::{
+    error::Error,
+    fs::{File, OpenOptions},
+    io::Write,
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::euchre::bots::Bot;
+
+use super::diversity::PopulationDiversity;
+use super::novelty::{Descriptor, DESCRIPTOR_NAMES};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsFormat {
+    // one JSON object per generation
+    #[default]
+    Jsonl,
+    // one row per generation, lists are joined with semicolons
+    Csv,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GenerationMetrics {
+    pub generation: usize,
+    // what the population was evaluated on, left out for whole games in matches
+    pub task: Option<String>,
+    // organisms of each lifetime in the evaluated population, from newborn up to the oldest
+    pub lifetime_counts: Vec<usize>,
+    pub mean_lifetime: f64,
+    pub mean_fitness: f64,
+    pub best_fitness: f64,
+    pub mean_connection_rate: f64,
+    // mean fractions of nodes using sigmoid, leaky relu and tanh
+    pub activation_rates: [f64; 3],
+    pub weight_spread: f64,
+    // population mean of each behaviour descriptor
+    pub mean_descriptor: Descriptor,
+    // organisms or elites in the novelty or MAP-Elites archive
+    pub archive_size: Option<usize>,
+    // left out when diversity_probes is 0
+    pub diversity: Option<PopulationDiversity>,
+    // population mean win rate against each baseline, in the configured order
+    pub baseline_win_rates: Vec<(Bot, f64)>,
+    // wall-clock seconds spent in each phase of the generation
+    pub play_seconds: f64,
+    pub baseline_seconds: f64,
+    pub diversity_seconds: f64,
+    pub breed_seconds: f64,
+    pub save_seconds: f64,
+}
+
+// Appends a line per generation to <out_dir>/metrics.jsonl or <out_dir>/metrics.csv
+pub struct MetricsLog {
+    file: File,
+    format: MetricsFormat,
+    header_written: bool,
+}
+
+impl FromStr for MetricsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "jsonl" => Ok(MetricsFormat::Jsonl),
+            "csv" => Ok(MetricsFormat::Csv),
+            _ => Err(format!("unknown metrics format {}", s)),
+        }
+    }
+}
+
+impl MetricsFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            MetricsFormat::Jsonl => "jsonl",
+            MetricsFormat::Csv => "csv",
+        }
+    }
+}
+
+impl GenerationMetrics {
+    // Named values in column order
+    fn fields(&self) -> Vec<(String, Value)> {
+        let mut fields = vec![("generation".to_string(), json!(self.generation))];
+        if let Some(task) = &self.task {
+            fields.push(("task".to_string(), json!(task)));
+        }
+        fields.extend([
+            ("lifetime_counts".to_string(), json!(self.lifetime_counts)),
+            ("mean_lifetime".to_string(), json!(self.mean_lifetime)),
+            ("mean_fitness".to_string(), json!(self.mean_fitness)),
+            ("best_fitness".to_string(), json!(self.best_fitness)),
+            (
+                "mean_connection_rate".to_string(),
+                json!(self.mean_connection_rate),
+            ),
+            ("sigmoid_rate".to_string(), json!(self.activation_rates[0])),
+            (
+                "leaky_relu_rate".to_string(),
+                json!(self.activation_rates[1]),
+            ),
+            ("tanh_rate".to_string(), json!(self.activation_rates[2])),
+            ("weight_spread".to_string(), json!(self.weight_spread)),
+        ]);
+        for (name, value) in DESCRIPTOR_NAMES.iter().zip(self.mean_descriptor) {
+            fields.push((format!("mean_{}", name), json!(value)));
+        }
+        if let Some(archive_size) = self.archive_size {
+            fields.push(("archive_size".to_string(), json!(archive_size)));
+        }
+        if let Some(diversity) = self.diversity {
+            fields.extend([
+                (
+                    "weight_distance".to_string(),
+                    json!(diversity.weight_distance),
+                ),
+                (
+                    "connection_distance".to_string(),
+                    json!(diversity.connection_distance),
+                ),
+                (
+                    "activation_distance".to_string(),
+                    json!(diversity.activation_distance),
+                ),
+                (
+                    "behavioural_distance".to_string(),
+                    json!(diversity.behavioural_distance),
+                ),
+                (
+                    "distinct_behaviours".to_string(),
+                    json!(diversity.distinct_behaviours),
+                ),
+            ]);
+        }
+        for (bot, win_rate) in self.baseline_win_rates.iter() {
+            fields.push((format!("win_rate_vs_{}", bot.name()), json!(win_rate)));
+        }
+        fields.extend([
+            ("play_seconds".to_string(), json!(self.play_seconds)),
+            ("baseline_seconds".to_string(), json!(self.baseline_seconds)),
+            (
+                "diversity_seconds".to_string(),
+                json!(self.diversity_seconds),
+            ),
+            ("breed_seconds".to_string(), json!(self.breed_seconds)),
+            ("save_seconds".to_string(), json!(self.save_seconds)),
+        ]);
+        fields
+    }
+}
+
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Array(values) => values
+            .iter()
+            .map(csv_value)
+            .collect::<Vec<String>>()
+            .join(";"),
+        _ => value.to_string(),
+    }
+}
+
+impl MetricsLog {
+    pub fn create(out_dir: &str, format: MetricsFormat) -> Result<MetricsLog, Box<dyn Error>> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(format!("{}/metrics.{}", out_dir, format.extension()))?;
+        Ok(MetricsLog {
+            file,
+            format,
+            header_written: false,
+        })
+    }
+
+    pub fn append(&mut self, metrics: &GenerationMetrics) -> Result<(), Box<dyn Error>> {
+        let fields = metrics.fields();
+        match self.format {
+            MetricsFormat::Jsonl => {
+                let object: Map<String, Value> = fields.into_iter().collect();
+                writeln!(self.file, "{}", Value::Object(object))?;
+            }
+            MetricsFormat::Csv => {
+                if !self.header_written {
+                    let header: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+                    writeln!(self.file, "{}", header.join(","))?;
+                    self.header_written = true;
+                }
+                let row: Vec<String> = fields.iter().map(|(_, value)| csv_value(value)).collect();
+                writeln!(self.file, "{}", row.join(","))?;
+            }
+        }
+        self.file.flush()?;
+        Ok(())
+    }
+}
@@ -1,3 +1,21 @@
+pub mod benchmark;
+pub mod config;
+pub mod curriculum;
+pub mod diversity;
+pub mod encoding;
+pub mod evaluation;
 pub mod evolution;
 mod helpers;
+pub mod metrics;
+pub mod mutation;
 pub mod neural_network;
+pub mod novelty;
+pub mod pareto;
+pub mod policy;
+pub mod quantized;
+pub mod reinforcement;
+pub mod selection;
+pub mod significance;
+pub mod symmetry;
+pub mod tournament;
+pub mod training;
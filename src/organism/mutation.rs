@@ -0,0 +1,92 @@
+use std::str::FromStr;
+
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MutationDistribution {
+    Uniform,
+    Gaussian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MutationSchedule {
+    Constant,
+    // scale falls linearly from 1 at the first generation to final_scale at the last
+    Linear { final_scale: f64 },
+    // scale is decay^(generation - 1)
+    Exponential { decay: f64 },
+}
+
+// Mutation settings resolved for a single generation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MutationParameters {
+    pub bias_rate: f64,
+    pub weight_rate: f64,
+    pub connection_rate: f64,
+    pub activation_rate: f64,
+    pub bias_magnitude: f64,
+    pub weight_magnitude: f64,
+    pub distribution: MutationDistribution,
+    pub self_adaptive: bool,
+    pub min_step_size: f64,
+}
+
+impl MutationSchedule {
+    pub fn scale(&self, generation: usize, generations: usize) -> f64 {
+        let elapsed = generation.saturating_sub(1) as f64;
+        match *self {
+            MutationSchedule::Constant => 1.0,
+            MutationSchedule::Linear { final_scale } => {
+                if generations <= 1 {
+                    return 1.0;
+                }
+                1.0 + (final_scale - 1.0) * elapsed / (generations - 1) as f64
+            }
+            MutationSchedule::Exponential { decay } => decay.powf(elapsed),
+        }
+    }
+}
+
+impl FromStr for MutationDistribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "uniform" => Ok(MutationDistribution::Uniform),
+            "gaussian" => Ok(MutationDistribution::Gaussian),
+            _ => Err(format!("unknown mutation distribution {}", s)),
+        }
+    }
+}
+
+impl MutationParameters {
+    pub fn perturbation<R: Rng>(&self, rng: &mut R, magnitude: f64) -> f64 {
+        match self.distribution {
+            MutationDistribution::Uniform => rng.gen_range(-magnitude..magnitude),
+            MutationDistribution::Gaussian => rng.sample::<f64, _>(StandardNormal) * magnitude,
+        }
+    }
+
+    // Log-normal self-adaptation of an evolution strategy step size, where global_noise is
+    // shared by every step size of the child and the learning rates depend on how many there are
+    pub fn adapt_step_size<R: Rng>(
+        &self,
+        rng: &mut R,
+        step_size: f64,
+        global_noise: f64,
+        step_size_count: usize,
+    ) -> f64 {
+        let n = step_size_count as f64;
+        let global_learning_rate = 1.0 / (2.0 * n).sqrt();
+        let local_learning_rate = 1.0 / (2.0 * n.sqrt()).sqrt();
+        let adapted = step_size
+            * (global_learning_rate * global_noise
+                + local_learning_rate * rng.sample::<f64, _>(StandardNormal))
+            .exp();
+        adapted.max(self.min_step_size)
+    }
+}
@@ -1,33 +1,33 @@
 use lazy_static::lazy_static;
 use rand::prelude::*;
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{Error, ErrorKind, Read, Write};
+use std::sync::Mutex;
 use strum::{EnumCount, IntoEnumIterator};
 use strum_macros::{EnumCount, EnumIter};
 
 use crate::euchre::enums::{ActionIndex, StateIndex};
+use crate::euchre::player::Player;
+use crate::organism::config::{InitConfig, NetworkConfig};
+use crate::organism::diversity::GenomeDistance;
+use crate::organism::encoding::{encode_events, InputEncoding, EVENT_INPUT_COUNT};
 use crate::organism::helpers::get_player_action;
+use crate::organism::mutation::MutationParameters;
+use crate::organism::policy::{all_finite, masked_argmax, masked_softmax, ActionPolicy};
+use crate::organism::training::DecisionRecord;
+use rand_distr::StandardNormal;
 
-const HIDDEN_NODES: usize = (StateIndex::COUNT + ActionIndex::COUNT) * 2 / 3;
+pub const DEFAULT_HIDDEN_NODES: usize = (StateIndex::COUNT + ActionIndex::COUNT) * 2 / 3;
 
 pub type AvailableActions = [bool; ActionIndex::COUNT];
 pub type NeuralNetworkInput = [f64; StateIndex::COUNT];
 
 lazy_static! {
-    static ref INITIAL_INDICES: [usize; ActionIndex::COUNT] = {
-        let mut indices = [0; ActionIndex::COUNT];
-        for i in 0..ActionIndex::COUNT {
-            indices[i] = i;
-        }
-        indices
-    };
     static ref ACTIVATION_FUNCTION_TYPES: [ActivationFunctionType; ActivationFunctionType::COUNT] = {
         let mut activation_function_types =
             [ActivationFunctionType::Sigmoid; ActivationFunctionType::COUNT];
-        let mut i = 0;
-        for aft in ActivationFunctionType::iter() {
+        for (i, aft) in ActivationFunctionType::iter().enumerate() {
             activation_function_types[i] = aft;
-            i += 1;
         }
         activation_function_types
     };
@@ -35,356 +35,1130 @@ lazy_static! {
 
 #[derive(PartialEq, Debug, Clone, Copy, Eq, EnumIter, EnumCount)]
 #[repr(C)]
-enum ActivationFunctionType {
+pub(super) enum ActivationFunctionType {
     Sigmoid,
     LeakyRelu,
     Tanh,
 }
 
-#[derive(PartialEq, Debug, Copy, Clone)]
-#[repr(C)]
+// Fully connected layer, weights and connections are indexed by input * nodes + node
+#[derive(PartialEq, Debug, Clone)]
+pub(super) struct Layer {
+    pub(super) inputs: usize,
+    pub(super) nodes: usize,
+    weights: Vec<f64>,
+    connections: Vec<bool>,
+    pub(super) biases: Vec<f64>,
+    pub(super) activation_functions: Vec<ActivationFunctionType>,
+    // self-adaptive mutation step sizes per node, zero until first used
+    step_sizes: Vec<f64>,
+    // weights with disabled connections zeroed, rebuilt whenever either changes
+    pub(super) masked_weights: Vec<f64>,
+}
+
+// Hidden layers in order followed by the output layer, a recurrent network's first layer also
+// takes its own previous outputs as inputs
+#[derive(PartialEq, Debug, Clone)]
 pub struct NeuralNetwork {
     pub tutor_mode: bool,
-    weights_input_hidden: [[f64; HIDDEN_NODES]; StateIndex::COUNT],
-    weights_hidden_output: [[f64; ActionIndex::COUNT]; HIDDEN_NODES],
-    connections_input_hidden: [[bool; HIDDEN_NODES]; StateIndex::COUNT],
-    connections_hidden_output: [[bool; ActionIndex::COUNT]; HIDDEN_NODES],
-    hidden_biases: [f64; HIDDEN_NODES],
+    recurrent: bool,
+    encoding: InputEncoding,
+    layers: Vec<Layer>,
+}
+
+// A network sitting at the table, carrying its recurrent state between decisions of a round
+pub struct Seat<'a> {
+    nn: &'a NeuralNetwork,
+    policy: ActionPolicy,
+    memory: Mutex<SeatMemory>,
+}
+
+struct SeatMemory {
+    state: Vec<f64>,
+    previous_inputs: Option<NeuralNetworkInput>,
+    // the first decision where an available action's output was NaN or infinite
+    non_finite: Option<DecisionRecord>,
+}
+
+// Accumulated loss gradients for the trainable parameters of each layer
+pub struct Gradients {
+    weights: Vec<Vec<f64>>,
+    biases: Vec<Vec<f64>>,
+}
+
+// Layout of networks saved before the versioned file format, which were the raw bytes of this
+// struct, only used to find where each field starts
+#[allow(dead_code)]
+#[repr(C)]
+struct LegacyNeuralNetwork {
+    tutor_mode: bool,
+    weights_input_hidden: [[f64; DEFAULT_HIDDEN_NODES]; StateIndex::COUNT],
+    weights_hidden_output: [[f64; ActionIndex::COUNT]; DEFAULT_HIDDEN_NODES],
+    connections_input_hidden: [[bool; DEFAULT_HIDDEN_NODES]; StateIndex::COUNT],
+    connections_hidden_output: [[bool; ActionIndex::COUNT]; DEFAULT_HIDDEN_NODES],
+    hidden_biases: [f64; DEFAULT_HIDDEN_NODES],
     final_biases: [f64; ActionIndex::COUNT],
-    hidden_activation_functions: [ActivationFunctionType; HIDDEN_NODES],
+    hidden_activation_functions: [ActivationFunctionType; DEFAULT_HIDDEN_NODES],
     final_activation_functions: [ActivationFunctionType; ActionIndex::COUNT],
+    hidden_step_sizes: [f64; DEFAULT_HIDDEN_NODES],
+    final_step_sizes: [f64; ActionIndex::COUNT],
 }
 
-impl NeuralNetwork {
-    pub fn new() -> Self {
-        let weights_input_hidden = [[0.0; HIDDEN_NODES]; StateIndex::COUNT];
-        let weights_hidden_output = [[0.0; ActionIndex::COUNT]; HIDDEN_NODES];
-        let connections_input_hidden = [[true; HIDDEN_NODES]; StateIndex::COUNT];
-        let connections_hidden_output = [[true; ActionIndex::COUNT]; HIDDEN_NODES];
-        let hidden_biases = [0.0; HIDDEN_NODES];
-        let final_biases = [0.0; ActionIndex::COUNT];
-        let hidden_activation_functions = [ActivationFunctionType::Sigmoid; HIDDEN_NODES];
-        let final_activation_functions = [ActivationFunctionType::Sigmoid; ActionIndex::COUNT];
+// legacy networks saved before step sizes were added to the genome end where they begin
+const LEGACY_NETWORK_SIZES: [usize; 2] = [
+    std::mem::size_of::<LegacyNeuralNetwork>(),
+    std::mem::offset_of!(LegacyNeuralNetwork, hidden_step_sizes),
+];
+const LEGACY_ACTIVATION_FUNCTION_SIZE: usize = std::mem::size_of::<ActivationFunctionType>();
 
-        NeuralNetwork {
-            tutor_mode: false,
-            weights_input_hidden,
-            weights_hidden_output,
-            connections_input_hidden,
-            connections_hidden_output,
-            hidden_biases,
-            final_biases,
-            hidden_activation_functions,
-            final_activation_functions,
+const FILE_MAGIC: &[u8; 8] = b"EUCHRENN";
+// version 1 had no recurrent flag, version 2 no input encoding
+const FILE_VERSION: u32 = 3;
+
+// Little endian cursor over a network file
+pub(super) struct ByteReader<'a> {
+    pub(super) bytes: &'a [u8],
+    pub(super) position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, count: usize) -> std::io::Result<&'a [u8]> {
+        if self.position + count > self.bytes.len() {
+            return Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "network file is truncated",
+            ));
         }
+        let bytes = &self.bytes[self.position..self.position + count];
+        self.position += count;
+        Ok(bytes)
     }
 
-    pub fn init(&mut self) {
-        let mut rng = rand::thread_rng();
-        // Hidden
-        for j in 0..HIDDEN_NODES {
-            // Hidden - Bias
-            self.hidden_biases[j] = rng.gen_range(-0.5..0.5);
-            // Hidden - Activation
-            self.hidden_activation_functions[j] =
-                ACTIVATION_FUNCTION_TYPES[rng.gen_range(0..ActivationFunctionType::COUNT)];
-            for i in 0..StateIndex::COUNT {
-                // Hidden - Weight
-                self.weights_input_hidden[i][j] = rng.gen_range(-0.5..0.5);
-                // Hidden - Connection
-                self.connections_input_hidden[i][j] = rng.gen::<f64>() < 0.5;
-            }
-        }
-        // Final
-        for j in 0..ActionIndex::COUNT {
-            // Final - Bias
-            self.final_biases[j] = rng.gen_range(-0.5..0.5);
-            // Final - Activation
-            self.final_activation_functions[j] =
-                ACTIVATION_FUNCTION_TYPES[rng.gen_range(0..ActivationFunctionType::COUNT)];
-            for i in 0..HIDDEN_NODES {
-                // Final - Weight
-                self.weights_hidden_output[i][j] = rng.gen_range(-0.5..0.5);
-                // Final - Connection
-                self.connections_hidden_output[i][j] = rng.gen::<f64>() < 0.5;
-            }
-        }
+    pub(super) fn read_u8(&mut self) -> std::io::Result<u8> {
+        Ok(self.take(1)?[0])
     }
 
-    fn sigmoid(x: f64) -> f64 {
-        1.0 / (1.0 + (-x).exp())
+    pub(super) fn read_u32(&mut self) -> std::io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
     }
 
-    fn leaky_relu(x: f64) -> f64 {
-        x.max(x * 0.01)
+    pub(super) fn read_f64(&mut self) -> std::io::Result<f64> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
     }
 
-    fn tanh(x: f64) -> f64 {
-        x.tanh()
+    pub(super) fn read_f32(&mut self) -> std::io::Result<f32> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
     }
 
-    pub fn crossover(
-        &self,
-        partner: &NeuralNetwork,
-        mutation_rate: f64,
-        mutation_magnitude: f64,
-    ) -> NeuralNetwork {
-        let mut rng = rand::thread_rng();
-        let mut child = NeuralNetwork::new();
+    pub(super) fn read_activation_function(&mut self) -> std::io::Result<ActivationFunctionType> {
+        ACTIVATION_FUNCTION_TYPES
+            .get(self.read_u8()? as usize)
+            .copied()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid activation"))
+    }
+}
 
-        // Combination
-        // Combination - Hidden
-        for j in 0..HIDDEN_NODES {
-            // Combination - Hidden - Bias
-            if rng.gen::<f64>() < 0.5 {
-                child.hidden_biases[j] = self.hidden_biases[j];
-            } else {
-                child.hidden_biases[j] = partner.hidden_biases[j];
-            }
-            // Combination - Hidden - Activation
-            if rng.gen::<f64>() < 0.5 {
-                child.hidden_activation_functions[j] = self.hidden_activation_functions[j];
-            } else {
-                child.hidden_activation_functions[j] = partner.hidden_activation_functions[j];
-            }
-            for i in 0..StateIndex::COUNT {
-                // Combination - Hidden - Weight and Connection
-                if rng.gen::<f64>() < 0.5 {
-                    child.weights_input_hidden[i][j] = self.weights_input_hidden[i][j];
-                    child.connections_input_hidden[i][j] = self.connections_input_hidden[i][j];
-                } else {
-                    child.weights_input_hidden[i][j] = partner.weights_input_hidden[i][j];
-                    child.connections_input_hidden[i][j] = partner.connections_input_hidden[i][j];
-                }
+impl Layer {
+    fn new(inputs: usize, nodes: usize) -> Self {
+        Layer {
+            inputs,
+            nodes,
+            weights: vec![0.0; inputs * nodes],
+            connections: vec![true; inputs * nodes],
+            biases: vec![0.0; nodes],
+            activation_functions: vec![ActivationFunctionType::Sigmoid; nodes],
+            step_sizes: vec![0.0; nodes],
+            masked_weights: vec![0.0; inputs * nodes],
+        }
+    }
+
+    fn mask_weights(&mut self) {
+        for ((masked, weight), connected) in self
+            .masked_weights
+            .iter_mut()
+            .zip(self.weights.iter())
+            .zip(self.connections.iter())
+        {
+            *masked = if *connected { *weight } else { 0.0 };
+        }
+    }
+
+    fn init(&mut self, config: &InitConfig, rng: &mut ThreadRng) {
+        let weight_range = config.weight_range;
+        let bias_range = config.bias_range;
+        for j in 0..self.nodes {
+            // Bias
+            self.biases[j] = rng.gen_range(-bias_range..bias_range);
+            // Activation
+            self.activation_functions[j] =
+                ACTIVATION_FUNCTION_TYPES[rng.gen_range(0..ActivationFunctionType::COUNT)];
+            for i in 0..self.inputs {
+                // Weight
+                self.weights[i * self.nodes + j] = rng.gen_range(-weight_range..weight_range);
+                // Connection
+                self.connections[i * self.nodes + j] =
+                    rng.gen::<f64>() < config.connection_probability;
             }
         }
-        // Combination - Final
-        for j in 0..ActionIndex::COUNT {
-            // Combination - Final - Bias
+        self.mask_weights();
+    }
+
+    fn crossover(&self, partner: &Layer, rng: &mut ThreadRng) -> Layer {
+        let mut child = Layer::new(self.inputs, self.nodes);
+        for j in 0..self.nodes {
+            // Bias
             if rng.gen::<f64>() < 0.5 {
-                child.final_biases[j] = self.final_biases[j];
+                child.biases[j] = self.biases[j];
             } else {
-                child.final_biases[j] = partner.final_biases[j];
+                child.biases[j] = partner.biases[j];
             }
-            // Combination - Final - Activation
+            // Activation
             if rng.gen::<f64>() < 0.5 {
-                child.final_activation_functions[j] = self.final_activation_functions[j];
+                child.activation_functions[j] = self.activation_functions[j];
             } else {
-                child.final_activation_functions[j] = partner.final_activation_functions[j];
+                child.activation_functions[j] = partner.activation_functions[j];
             }
-            for i in 0..HIDDEN_NODES {
-                // Combination - Final - Weight and Connection
+            // Step Size
+            child.step_sizes[j] = (self.step_sizes[j] + partner.step_sizes[j]) / 2.0;
+            for i in 0..self.inputs {
+                // Weight and Connection
+                let k = i * self.nodes + j;
                 if rng.gen::<f64>() < 0.5 {
-                    child.weights_hidden_output[i][j] = self.weights_hidden_output[i][j];
-                    child.connections_hidden_output[i][j] = self.connections_hidden_output[i][j];
+                    child.weights[k] = self.weights[k];
+                    child.connections[k] = self.connections[k];
                 } else {
-                    child.weights_hidden_output[i][j] = partner.weights_hidden_output[i][j];
-                    child.connections_hidden_output[i][j] = partner.connections_hidden_output[i][j];
+                    child.weights[k] = partner.weights[k];
+                    child.connections[k] = partner.connections[k];
                 }
             }
         }
+        child.mask_weights();
+        child
+    }
 
-        // Mutation
-        // Mutation - Hidden
-        for j in 0..HIDDEN_NODES {
-            // Mutation - Hidden - Bias
-            if rng.gen::<f64>() < mutation_rate {
-                child.hidden_biases[j] += rng.gen_range(-mutation_magnitude..mutation_magnitude);
-            }
-            // Mutation - Hidden - Activation
-            if rng.gen::<f64>() < mutation_rate {
-                child.hidden_activation_functions[j] =
+    fn mutate(&mut self, mutation: &MutationParameters, rng: &mut ThreadRng) {
+        for j in 0..self.nodes {
+            let (bias_magnitude, weight_magnitude) = if mutation.self_adaptive {
+                (self.step_sizes[j], self.step_sizes[j])
+            } else {
+                (mutation.bias_magnitude, mutation.weight_magnitude)
+            };
+            // Bias
+            if rng.gen::<f64>() < mutation.bias_rate {
+                self.biases[j] += mutation.perturbation(rng, bias_magnitude);
+            }
+            // Activation
+            if rng.gen::<f64>() < mutation.activation_rate {
+                self.activation_functions[j] =
                     ACTIVATION_FUNCTION_TYPES[rng.gen_range(0..ActivationFunctionType::COUNT)];
             }
-            for i in 0..StateIndex::COUNT {
-                // Mutation - Hidden - Connection
-                if rng.gen::<f64>() < mutation_rate {
-                    child.connections_input_hidden[i][j] = !child.connections_input_hidden[i][j];
+            for i in 0..self.inputs {
+                let k = i * self.nodes + j;
+                // Connection
+                if rng.gen::<f64>() < mutation.connection_rate {
+                    self.connections[k] = !self.connections[k];
                 }
-                // Mutation - Hidden - Weight
-                if child.connections_input_hidden[i][j] && rng.gen::<f64>() < mutation_rate {
-                    child.weights_input_hidden[i][j] +=
-                        rng.gen_range(-mutation_magnitude..mutation_magnitude);
+                // Weight
+                if self.connections[k] && rng.gen::<f64>() < mutation.weight_rate {
+                    self.weights[k] += mutation.perturbation(rng, weight_magnitude);
                 }
             }
         }
-        // Mutation - Final
-        for j in 0..ActionIndex::COUNT {
-            // Mutation - Final - Bias
-            if rng.gen::<f64>() < mutation_rate {
-                child.final_biases[j] += rng.gen_range(-mutation_magnitude..mutation_magnitude);
+        self.mask_weights();
+    }
+
+    // Weighted sums before activation, and outputs, only visiting the rows of non-zero inputs
+    // since the game inputs are almost all zero
+    fn forward(&self, inputs: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        let mut sums = self.biases.clone();
+        for (i, &input) in inputs[..self.inputs].iter().enumerate() {
+            if input == 0.0 {
+                continue;
             }
-            // Mutation - Final - Activation
-            if rng.gen::<f64>() < mutation_rate {
-                child.final_activation_functions[j] =
-                    ACTIVATION_FUNCTION_TYPES[rng.gen_range(0..ActivationFunctionType::COUNT)];
+            let row = &self.masked_weights[i * self.nodes..(i + 1) * self.nodes];
+            add_scaled(&mut sums, row, input);
+        }
+        let outputs = self.activations(&sums);
+        (sums, outputs)
+    }
+
+    // The same as forward for many inputs, reading each row of weights once for the whole batch
+    fn forward_batch(&self, inputs: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let mut sums = vec![self.biases.clone(); inputs.len()];
+        for i in 0..self.inputs {
+            let row = &self.masked_weights[i * self.nodes..(i + 1) * self.nodes];
+            for (input, sums) in inputs.iter().zip(sums.iter_mut()) {
+                if input[i] == 0.0 {
+                    continue;
+                }
+                add_scaled(sums, row, input[i]);
             }
-            for i in 0..HIDDEN_NODES {
-                // Mutation - Final - Connection
-                if rng.gen::<f64>() < mutation_rate {
-                    child.connections_hidden_output[i][j] = !child.connections_hidden_output[i][j];
+        }
+        sums.iter().map(|sums| self.activations(sums)).collect()
+    }
+
+    fn activations(&self, sums: &[f64]) -> Vec<f64> {
+        sums.iter()
+            .zip(self.activation_functions.iter())
+            .map(|(&sum, &activation_function)| NeuralNetwork::activate(activation_function, sum))
+            .collect()
+    }
+
+    // Dense weights and connection checks for every edge, kept to benchmark against
+    fn forward_reference(&self, inputs: &[f64]) -> Vec<f64> {
+        (0..self.nodes)
+            .map(|j| {
+                let mut sum = self.biases[j];
+                for (i, input) in inputs.iter().enumerate().take(self.inputs) {
+                    if self.connections[i * self.nodes + j] {
+                        sum += input * self.weights[i * self.nodes + j];
+                    }
                 }
-                // Mutation - Final - Weight
-                if child.connections_hidden_output[i][j] && rng.gen::<f64>() < mutation_rate {
-                    child.weights_hidden_output[i][j] +=
-                        rng.gen_range(-mutation_magnitude..mutation_magnitude);
+                NeuralNetwork::activate(self.activation_functions[j], sum)
+            })
+            .collect()
+    }
+}
+
+// sums += scale * row, four lanes at a time where the CPU has AVX. Lanes multiply then add
+// like the scalar loop, so both give the same outputs
+fn add_scaled(sums: &mut [f64], row: &[f64], scale: f64) {
+    #[cfg(target_arch = "x86_64")]
+    if is_x86_feature_detected!("avx") {
+        // SAFETY: AVX support was checked just above
+        unsafe { add_scaled_avx(sums, row, scale) };
+        return;
+    }
+    for (sum, weight) in sums.iter_mut().zip(row) {
+        *sum += scale * weight;
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx")]
+unsafe fn add_scaled_avx(sums: &mut [f64], row: &[f64], scale: f64) {
+    use std::arch::x86_64::{
+        _mm256_add_pd, _mm256_loadu_pd, _mm256_mul_pd, _mm256_set1_pd, _mm256_storeu_pd,
+    };
+
+    let len = sums.len().min(row.len());
+    let lanes = len - len % 4;
+    let scales = _mm256_set1_pd(scale);
+    for k in (0..lanes).step_by(4) {
+        let sum = _mm256_loadu_pd(sums.as_ptr().add(k));
+        let weight = _mm256_loadu_pd(row.as_ptr().add(k));
+        _mm256_storeu_pd(
+            sums.as_mut_ptr().add(k),
+            _mm256_add_pd(sum, _mm256_mul_pd(scales, weight)),
+        );
+    }
+    for (sum, weight) in sums[lanes..len].iter_mut().zip(&row[lanes..len]) {
+        *sum += scale * weight;
+    }
+}
+
+impl NeuralNetwork {
+    // Default shape with a single hidden layer
+    pub fn new() -> Self {
+        NeuralNetwork::from_config(&NetworkConfig::default())
+    }
+
+    pub fn from_config(config: &NetworkConfig) -> Self {
+        let mut layers = Vec::with_capacity(config.hidden_layers.len() + 1);
+        let mut inputs = if config.recurrent {
+            EVENT_INPUT_COUNT + config.hidden_layers[0]
+        } else {
+            config.encoding.input_count()
+        };
+        for &nodes in config
+            .hidden_layers
+            .iter()
+            .chain([ActionIndex::COUNT].iter())
+        {
+            layers.push(Layer::new(inputs, nodes));
+            inputs = nodes;
+        }
+        NeuralNetwork {
+            tutor_mode: false,
+            recurrent: config.recurrent,
+            encoding: config.encoding,
+            layers,
+        }
+    }
+
+    pub fn config(&self) -> NetworkConfig {
+        NetworkConfig {
+            hidden_layers: self.hidden_layers(),
+            recurrent: self.recurrent,
+            encoding: self.encoding,
+        }
+    }
+
+    pub fn hidden_layers(&self) -> Vec<usize> {
+        self.layers[..self.layers.len() - 1]
+            .iter()
+            .map(|layer| layer.nodes)
+            .collect()
+    }
+
+    pub fn is_recurrent(&self) -> bool {
+        self.recurrent
+    }
+
+    pub fn encoding(&self) -> InputEncoding {
+        self.encoding
+    }
+
+    pub(super) fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    // Inputs of the first layer that come from the game rather than the recurrent state
+    fn input_count(&self) -> usize {
+        if self.recurrent {
+            EVENT_INPUT_COUNT
+        } else {
+            self.encoding.input_count()
+        }
+    }
+
+    pub fn init(&mut self, config: &InitConfig) {
+        let mut rng = rand::thread_rng();
+        for layer in self.layers.iter_mut() {
+            layer.init(config, &mut rng);
+        }
+    }
+
+    fn sigmoid(x: f64) -> f64 {
+        1.0 / (1.0 + (-x).exp())
+    }
+
+    fn leaky_relu(x: f64) -> f64 {
+        x.max(x * 0.01)
+    }
+
+    fn tanh(x: f64) -> f64 {
+        x.tanh()
+    }
+
+    // Fails if the networks have different shapes
+    pub fn crossover(
+        &self,
+        partner: &NeuralNetwork,
+        mutation: &MutationParameters,
+    ) -> Result<NeuralNetwork, String> {
+        if self.config() != partner.config() {
+            return Err(format!(
+                "can't cross {:?} with {:?}",
+                self.config(),
+                partner.config()
+            ));
+        }
+        let mut rng = rand::thread_rng();
+
+        // Combination
+        let mut child = NeuralNetwork {
+            tutor_mode: false,
+            recurrent: self.recurrent,
+            encoding: self.encoding,
+            layers: self
+                .layers
+                .iter()
+                .zip(partner.layers.iter())
+                .map(|(layer, partner_layer)| layer.crossover(partner_layer, &mut rng))
+                .collect(),
+        };
+
+        // Mutation
+        // Mutation - Step Sizes
+        if mutation.self_adaptive {
+            let global_noise = rng.sample::<f64, _>(StandardNormal);
+            let step_size_count = child.layers.iter().map(|layer| layer.nodes).sum();
+            for step_size in child
+                .layers
+                .iter_mut()
+                .flat_map(|layer| layer.step_sizes.iter_mut())
+            {
+                if *step_size <= 0.0 {
+                    *step_size = mutation.weight_magnitude;
                 }
+                *step_size =
+                    mutation.adapt_step_size(&mut rng, *step_size, global_noise, step_size_count);
             }
         }
+        // Mutation - Layers
+        for layer in child.layers.iter_mut() {
+            layer.mutate(mutation, &mut rng);
+        }
 
-        child
+        Ok(child)
+    }
+
+    // A recurrent network's state is replaced by the new outputs of the first layer
+    fn query(&self, inputs: &[f64], state: &mut Vec<f64>) -> [f64; ActionIndex::COUNT] {
+        let mut outputs = inputs.to_vec();
+        if self.recurrent {
+            outputs.extend_from_slice(state);
+        }
+        for (l, layer) in self.layers.iter().enumerate() {
+            outputs = layer.forward(&outputs).1;
+            if l == 0 && self.recurrent {
+                state.clone_from(&outputs);
+            }
+        }
+        let mut final_outputs = [0.0; ActionIndex::COUNT];
+        final_outputs.copy_from_slice(&outputs);
+        final_outputs
+    }
+
+    // Queries with the inputs in the network's encoding, returning the outputs by ActionIndex
+    fn query_encoded(
+        &self,
+        inputs: &NeuralNetworkInput,
+        state: &mut Vec<f64>,
+    ) -> [f64; ActionIndex::COUNT] {
+        let (encoded, actions) = self.encoding.encode(inputs);
+        NeuralNetwork::by_action(&self.query(&encoded, state), &actions)
     }
 
-    fn query(&self, inputs: &NeuralNetworkInput) -> [f64; ActionIndex::COUNT] {
-        let mut hidden_outputs = [0.0; HIDDEN_NODES];
+    // Outputs of the encoding's actions in ActionIndex order
+    fn by_action(
+        outputs: &[f64],
+        actions: &[usize; ActionIndex::COUNT],
+    ) -> [f64; ActionIndex::COUNT] {
         let mut final_outputs = [0.0; ActionIndex::COUNT];
+        for (output, &action) in outputs.iter().zip(actions.iter()) {
+            final_outputs[action] = *output;
+        }
+        final_outputs
+    }
 
-        for i in 0..HIDDEN_NODES {
-            let mut sum = self.hidden_biases[i];
-            for j in 0..StateIndex::COUNT {
-                if self.connections_input_hidden[j][i] {
-                    sum += inputs[j] * self.weights_input_hidden[j][i];
-                }
-            }
-            match self.hidden_activation_functions[i] {
-                ActivationFunctionType::Sigmoid => hidden_outputs[i] = NeuralNetwork::sigmoid(sum),
-                ActivationFunctionType::LeakyRelu => {
-                    hidden_outputs[i] = NeuralNetwork::leaky_relu(sum)
+    // Only for networks without a recurrent state
+    pub fn outputs(&self, inputs: &NeuralNetworkInput) -> [f64; ActionIndex::COUNT] {
+        assert!(!self.recurrent, "recurrent networks need a seat");
+        self.query_encoded(inputs, &mut Vec::new())
+    }
+
+    // outputs for many inputs at once
+    pub fn outputs_batch(&self, inputs: &[NeuralNetworkInput]) -> Vec<[f64; ActionIndex::COUNT]> {
+        assert!(!self.recurrent, "recurrent networks need a seat");
+        let (mut outputs, actions): (Vec<Vec<f64>>, Vec<[usize; ActionIndex::COUNT]>) = inputs
+            .iter()
+            .map(|inputs| self.encoding.encode(inputs))
+            .unzip();
+        for layer in self.layers.iter() {
+            outputs = layer.forward_batch(&outputs);
+        }
+        outputs
+            .iter()
+            .zip(actions.iter())
+            .map(|(outputs, actions)| NeuralNetwork::by_action(outputs, actions))
+            .collect()
+    }
+
+    // outputs through every edge of the dense weights, to benchmark against
+    pub fn outputs_reference(&self, inputs: &NeuralNetworkInput) -> [f64; ActionIndex::COUNT] {
+        assert!(!self.recurrent, "recurrent networks need a seat");
+        let (mut outputs, actions) = self.encoding.encode(inputs);
+        for layer in self.layers.iter() {
+            outputs = layer.forward_reference(&outputs);
+        }
+        NeuralNetwork::by_action(&outputs, &actions)
+    }
+
+    pub fn seat(&self) -> Seat<'_> {
+        self.seat_with_policy(ActionPolicy::Argmax)
+    }
+
+    pub fn seat_with_policy(&self, policy: ActionPolicy) -> Seat<'_> {
+        Seat {
+            nn: self,
+            policy,
+            memory: Mutex::new(SeatMemory {
+                state: self.initial_state(),
+                previous_inputs: None,
+                non_finite: None,
+            }),
+        }
+    }
+
+    fn initial_state(&self) -> Vec<f64> {
+        if self.recurrent {
+            vec![0.0; self.layers[0].nodes]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn choose_action(
+        &self,
+        inputs: &NeuralNetworkInput,
+        final_outputs: &[f64; ActionIndex::COUNT],
+        available_actions: &AvailableActions,
+        policy: ActionPolicy,
+    ) -> ActionIndex {
+        let greedy = masked_argmax(final_outputs, available_actions);
+        // sampling can't weigh NaN or infinite outputs, so those decisions stay greedy over the
+        // finite ones
+        let action_index = if all_finite(final_outputs, available_actions) {
+            policy.choose(
+                final_outputs,
+                available_actions,
+                greedy,
+                &mut rand::thread_rng(),
+            )
+        } else {
+            greedy
+        };
+        if self.tutor_mode {
+            return get_player_action(
+                inputs,
+                available_actions,
+                &ActionIndex::from_usize(action_index),
+            );
+        }
+        ActionIndex::from_usize(action_index)
+    }
+
+    pub(super) fn activate(activation_function: ActivationFunctionType, x: f64) -> f64 {
+        match activation_function {
+            ActivationFunctionType::Sigmoid => NeuralNetwork::sigmoid(x),
+            ActivationFunctionType::LeakyRelu => NeuralNetwork::leaky_relu(x),
+            ActivationFunctionType::Tanh => NeuralNetwork::tanh(x),
+        }
+    }
+
+    // Derivative of the activation function given its input x and output y
+    fn activation_derivative(activation_function: ActivationFunctionType, x: f64, y: f64) -> f64 {
+        match activation_function {
+            ActivationFunctionType::Sigmoid => y * (1.0 - y),
+            ActivationFunctionType::LeakyRelu => {
+                if x > 0.0 {
+                    1.0
+                } else {
+                    0.01
                 }
-                ActivationFunctionType::Tanh => hidden_outputs[i] = NeuralNetwork::tanh(sum),
             }
+            ActivationFunctionType::Tanh => 1.0 - y * y,
         }
+    }
 
-        for i in 0..ActionIndex::COUNT {
-            let mut sum = self.final_biases[i];
-            for j in 0..HIDDEN_NODES {
-                if self.connections_hidden_output[j][i] {
-                    sum += hidden_outputs[j] * self.weights_hidden_output[j][i];
+    // Only for networks without a recurrent state
+    pub fn action_probabilities(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+        temperature: f64,
+    ) -> [f64; ActionIndex::COUNT] {
+        masked_softmax(&self.outputs(inputs), available_actions, temperature)
+    }
+
+    // Masked softmax cross-entropy of the final outputs against the chosen action, adding the
+    // gradients scaled by weight to gradients and returning the loss and whether the network
+    // agrees with the choice, recurrent networks are not supported
+    pub fn backpropagate(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+        action: ActionIndex,
+        temperature: f64,
+        weight: f64,
+        gradients: &mut Gradients,
+    ) -> (f64, bool) {
+        assert!(
+            !self.recurrent,
+            "can't backpropagate through a recurrent network"
+        );
+        let (encoded, actions) = self.encoding.encode(inputs);
+        // layer_inputs[l] feeds layers[l], the last entry is the network output
+        let mut layer_inputs: Vec<Vec<f64>> = vec![encoded];
+        let mut layer_sums: Vec<Vec<f64>> = Vec::with_capacity(self.layers.len());
+        for layer in self.layers.iter() {
+            let (sums, outputs) = layer.forward(&layer_inputs[layer_inputs.len() - 1]);
+            layer_sums.push(sums);
+            layer_inputs.push(outputs);
+        }
+        let final_outputs = NeuralNetwork::by_action(&layer_inputs[self.layers.len()], &actions);
+
+        let action = action as usize;
+        let (loss, agrees, probabilities) =
+            NeuralNetwork::cross_entropy(&final_outputs, available_actions, action, temperature);
+
+        // gradient of the loss with respect to each output of the current layer
+        let mut errors: Vec<f64> = actions
+            .iter()
+            .map(|&i| {
+                if !available_actions[i] {
+                    return 0.0;
                 }
+                let target = if i == action { 1.0 } else { 0.0 };
+                weight * (probabilities[i] - target) / temperature
+            })
+            .collect();
+        for l in (0..self.layers.len()).rev() {
+            let layer = &self.layers[l];
+            let layer_input = &layer_inputs[l];
+            let deltas: Vec<f64> = (0..layer.nodes)
+                .map(|j| {
+                    errors[j]
+                        * NeuralNetwork::activation_derivative(
+                            layer.activation_functions[j],
+                            layer_sums[l][j],
+                            layer_inputs[l + 1][j],
+                        )
+                })
+                .collect();
+            for (bias, delta) in gradients.biases[l].iter_mut().zip(deltas.iter()) {
+                *bias += delta;
             }
-            match self.final_activation_functions[i] {
-                ActivationFunctionType::Sigmoid => final_outputs[i] = NeuralNetwork::sigmoid(sum),
-                ActivationFunctionType::LeakyRelu => {
-                    final_outputs[i] = NeuralNetwork::leaky_relu(sum)
+            let mut input_errors = vec![0.0; layer.inputs];
+            for i in 0..layer.inputs {
+                // zero inputs contribute no weight gradients, and network inputs need no errors
+                if l == 0 && layer_input[i] == 0.0 {
+                    continue;
+                }
+                for (j, delta) in deltas.iter().enumerate() {
+                    let k = i * layer.nodes + j;
+                    if layer.connections[k] {
+                        gradients.weights[l][k] += layer_input[i] * delta;
+                        input_errors[i] += layer.weights[k] * delta;
+                    }
                 }
-                ActivationFunctionType::Tanh => final_outputs[i] = NeuralNetwork::tanh(sum),
             }
+            errors = input_errors;
         }
 
-        final_outputs
+        (loss, agrees)
     }
 
-    pub fn get_action(
+    // The loss and agreement of backpropagate alone, without keeping what gradients need
+    pub fn loss(
         &self,
         inputs: &NeuralNetworkInput,
         available_actions: &AvailableActions,
-    ) -> ActionIndex {
-        let final_outputs = self.query(inputs);
-        let mut indices: [usize; ActionIndex::COUNT] = INITIAL_INDICES.clone();
-        indices.sort_by(|&a, &b| final_outputs[b].partial_cmp(&final_outputs[a]).unwrap());
-        for action_index in indices {
-            if available_actions[action_index] {
-                if self.tutor_mode {
-                    return get_player_action(
-                        inputs,
-                        available_actions,
-                        &ActionIndex::from_usize(action_index),
-                    );
-                }
-                return ActionIndex::from_usize(action_index);
+        action: ActionIndex,
+        temperature: f64,
+    ) -> (f64, bool) {
+        let final_outputs = self.outputs(inputs);
+        let (loss, agrees, _) = NeuralNetwork::cross_entropy(
+            &final_outputs,
+            available_actions,
+            action as usize,
+            temperature,
+        );
+        (loss, agrees)
+    }
+
+    // Masked softmax cross-entropy against the chosen action, whether the highest available
+    // output is that action, and the probabilities the loss was taken from
+    fn cross_entropy(
+        final_outputs: &[f64; ActionIndex::COUNT],
+        available_actions: &AvailableActions,
+        action: usize,
+        temperature: f64,
+    ) -> (f64, bool, [f64; ActionIndex::COUNT]) {
+        let mut predicted = action;
+        let mut max_output = f64::NEG_INFINITY;
+        for i in 0..ActionIndex::COUNT {
+            if available_actions[i] && final_outputs[i] > max_output {
+                max_output = final_outputs[i];
+                predicted = i;
             }
         }
-        panic!("No available actions!")
+        let probabilities = masked_softmax(final_outputs, available_actions, temperature);
+        let loss = -probabilities[action].max(f64::MIN_POSITIVE).ln();
+        (loss, predicted == action, probabilities)
     }
 
-    fn to_bytes(&self) -> &[u8] {
-        let raw_bytes: &[u8] = unsafe {
-            std::slice::from_raw_parts(
-                self as *const _ as *const u8,
-                std::mem::size_of::<NeuralNetwork>(),
-            )
+    // Gradient descent step, leaving the gradients zeroed
+    pub fn apply_gradients(&mut self, gradients: &mut Gradients, learning_rate: f64) {
+        for (l, layer) in self.layers.iter_mut().enumerate() {
+            for (weight, gradient) in layer.weights.iter_mut().zip(gradients.weights[l].iter()) {
+                *weight -= learning_rate * gradient;
+            }
+            for (bias, gradient) in layer.biases.iter_mut().zip(gradients.biases[l].iter()) {
+                *bias -= learning_rate * gradient;
+            }
+            layer.mask_weights();
+        }
+        gradients.clear();
+    }
+
+    // Little endian, magic and version, input count, recurrent flag, input encoding, layer count
+    // and widths, then each layer's weights, connections, biases, activation functions and step
+    // sizes
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(FILE_MAGIC);
+        bytes.extend_from_slice(&FILE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.input_count() as u32).to_le_bytes());
+        bytes.push(self.recurrent as u8);
+        bytes.push(self.encoding as u8);
+        bytes.extend_from_slice(&(self.layers.len() as u32).to_le_bytes());
+        for layer in self.layers.iter() {
+            bytes.extend_from_slice(&(layer.nodes as u32).to_le_bytes());
+        }
+        for layer in self.layers.iter() {
+            for weight in layer.weights.iter() {
+                bytes.extend_from_slice(&weight.to_le_bytes());
+            }
+            for connection in layer.connections.iter() {
+                bytes.push(*connection as u8);
+            }
+            for bias in layer.biases.iter() {
+                bytes.extend_from_slice(&bias.to_le_bytes());
+            }
+            for activation_function in layer.activation_functions.iter() {
+                bytes.push(*activation_function as u8);
+            }
+            for step_size in layer.step_sizes.iter() {
+                bytes.extend_from_slice(&step_size.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> std::io::Result<NeuralNetwork> {
+        if !bytes.starts_with(FILE_MAGIC) {
+            if LEGACY_NETWORK_SIZES.contains(&bytes.len()) {
+                return NeuralNetwork::from_legacy_bytes(bytes);
+            }
+            return Err(Error::new(ErrorKind::InvalidData, "not a network file"));
+        }
+        let mut reader = ByteReader {
+            bytes,
+            position: FILE_MAGIC.len(),
         };
-        raw_bytes
+        let version = reader.read_u32()?;
+        if version == 0 || version > FILE_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unsupported network file version {}", version),
+            ));
+        }
+        let input_count = reader.read_u32()? as usize;
+        let recurrent = version >= 2 && reader.read_u8()? != 0;
+        let encoding = if version >= 3 {
+            let value = reader.read_u8()?;
+            InputEncoding::from_u8(value).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unknown input encoding {}", value),
+                )
+            })?
+        } else {
+            InputEncoding::Absolute
+        };
+        let layer_count = reader.read_u32()? as usize;
+        let mut widths = Vec::with_capacity(layer_count);
+        for _ in 0..layer_count {
+            widths.push(reader.read_u32()? as usize);
+        }
+        if widths.last() != Some(&ActionIndex::COUNT)
+            || widths.contains(&0)
+            || (recurrent && layer_count < 2)
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid layer widths {:?}", widths),
+            ));
+        }
+        if recurrent && encoding != InputEncoding::Absolute {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "recurrent networks only take the absolute encoding",
+            ));
+        }
+        let mut network = NeuralNetwork::from_config(&NetworkConfig {
+            hidden_layers: widths[..layer_count - 1].to_vec(),
+            recurrent,
+            encoding,
+        });
+        if input_count != network.input_count() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "network has {} inputs, expected {}",
+                    input_count,
+                    network.input_count()
+                ),
+            ));
+        }
+        for layer in network.layers.iter_mut() {
+            for weight in layer.weights.iter_mut() {
+                *weight = reader.read_f64()?;
+            }
+            for connection in layer.connections.iter_mut() {
+                *connection = reader.read_u8()? != 0;
+            }
+            for bias in layer.biases.iter_mut() {
+                *bias = reader.read_f64()?;
+            }
+            for activation_function in layer.activation_functions.iter_mut() {
+                *activation_function = reader.read_activation_function()?;
+            }
+            for step_size in layer.step_sizes.iter_mut() {
+                *step_size = reader.read_f64()?;
+            }
+            layer.mask_weights();
+        }
+        Ok(network)
     }
 
-    fn from_bytes(bytes: &[u8]) -> NeuralNetwork {
-        assert_eq!(bytes.len(), std::mem::size_of::<NeuralNetwork>());
-        unsafe { std::ptr::read(bytes.as_ptr() as *const NeuralNetwork) }
+    // Raw native endian bytes of a LegacyNeuralNetwork, possibly without step sizes
+    fn from_legacy_bytes(bytes: &[u8]) -> std::io::Result<NeuralNetwork> {
+        use std::mem::offset_of;
+        let read_f64 =
+            |offset: usize| f64::from_ne_bytes(bytes[offset..offset + 8].try_into().unwrap());
+        let read_activation = |offset: usize| {
+            let mut value = [0; LEGACY_ACTIVATION_FUNCTION_SIZE];
+            value.copy_from_slice(&bytes[offset..offset + LEGACY_ACTIVATION_FUNCTION_SIZE]);
+            ACTIVATION_FUNCTION_TYPES
+                .get(u32::from_ne_bytes(value) as usize)
+                .copied()
+                .ok_or_else(|| Error::new(ErrorKind::InvalidData, "invalid activation"))
+        };
+        let has_step_sizes = bytes.len() == LEGACY_NETWORK_SIZES[0];
+        let mut network = NeuralNetwork::new();
+        let (hidden, final_layer) = network.layers.split_at_mut(1);
+        let (hidden, final_layer) = (&mut hidden[0], &mut final_layer[0]);
+        for (layer, weights, connections, biases, activation_functions, step_sizes) in [
+            (
+                hidden,
+                offset_of!(LegacyNeuralNetwork, weights_input_hidden),
+                offset_of!(LegacyNeuralNetwork, connections_input_hidden),
+                offset_of!(LegacyNeuralNetwork, hidden_biases),
+                offset_of!(LegacyNeuralNetwork, hidden_activation_functions),
+                offset_of!(LegacyNeuralNetwork, hidden_step_sizes),
+            ),
+            (
+                final_layer,
+                offset_of!(LegacyNeuralNetwork, weights_hidden_output),
+                offset_of!(LegacyNeuralNetwork, connections_hidden_output),
+                offset_of!(LegacyNeuralNetwork, final_biases),
+                offset_of!(LegacyNeuralNetwork, final_activation_functions),
+                offset_of!(LegacyNeuralNetwork, final_step_sizes),
+            ),
+        ] {
+            // the legacy arrays were [input][node], the same order as the layer
+            for k in 0..layer.weights.len() {
+                layer.weights[k] = read_f64(weights + 8 * k);
+                layer.connections[k] = bytes[connections + k] != 0;
+            }
+            for j in 0..layer.nodes {
+                layer.biases[j] = read_f64(biases + 8 * j);
+                layer.activation_functions[j] =
+                    read_activation(activation_functions + LEGACY_ACTIVATION_FUNCTION_SIZE * j)?;
+                if has_step_sizes {
+                    layer.step_sizes[j] = read_f64(step_sizes + 8 * j);
+                }
+            }
+            layer.mask_weights();
+        }
+        Ok(network)
     }
 
     pub fn save_to_file(&self, filename: &str) -> std::io::Result<()> {
         let mut file = File::create(filename)?;
-        let bytes = self.to_bytes();
-        file.write_all(&bytes)?;
+        file.write_all(&self.to_bytes())?;
         Ok(())
     }
 
+    // Takes on the shape of the network in the file
     pub fn load_from_file(&mut self, filename: &str) -> std::io::Result<()> {
         let mut file = File::open(filename)?;
         let mut bytes = Vec::new();
         file.read_to_end(&mut bytes)?;
-        let in_network = NeuralNetwork::from_bytes(&bytes);
-        self.weights_input_hidden = in_network.weights_input_hidden;
-        self.weights_hidden_output = in_network.weights_hidden_output;
-        self.connections_input_hidden = in_network.connections_input_hidden;
-        self.connections_hidden_output = in_network.connections_hidden_output;
-        self.hidden_biases = in_network.hidden_biases;
-        self.final_biases = in_network.final_biases;
-        self.hidden_activation_functions = in_network.hidden_activation_functions;
-        self.final_activation_functions = in_network.final_activation_functions;
+        let in_network = NeuralNetwork::from_bytes(&bytes)?;
+        self.recurrent = in_network.recurrent;
+        self.encoding = in_network.encoding;
+        self.layers = in_network.layers;
         Ok(())
     }
 
-    pub fn stats(&self) {
+    // Enabled connections and total possible connections
+    pub fn connection_count(&self) -> (usize, usize) {
         let mut connected_count = 0;
-        let mut disconnected_count = 0;
-        let mut sigmoid_count = 0;
-        let mut leaky_relu_count = 0;
-        let mut tanh_count = 0;
-        for j in 0..HIDDEN_NODES {
-            match self.hidden_activation_functions[j] {
-                ActivationFunctionType::Sigmoid => sigmoid_count += 1,
-                ActivationFunctionType::LeakyRelu => leaky_relu_count += 1,
-                ActivationFunctionType::Tanh => tanh_count += 1,
-            }
-            for i in 0..StateIndex::COUNT {
-                match self.connections_input_hidden[i][j] {
-                    true => connected_count += 1,
-                    false => disconnected_count += 1,
-                }
-            }
+        let mut total_count = 0;
+        for layer in self.layers.iter() {
+            connected_count += layer.connections.iter().filter(|&&c| c).count();
+            total_count += layer.connections.len();
         }
-        for j in 0..ActionIndex::COUNT {
-            match self.final_activation_functions[j] {
-                ActivationFunctionType::Sigmoid => sigmoid_count += 1,
-                ActivationFunctionType::LeakyRelu => leaky_relu_count += 1,
-                ActivationFunctionType::Tanh => tanh_count += 1,
+        (connected_count, total_count)
+    }
+
+    pub fn connection_rate(&self) -> f64 {
+        let (connected_count, total_count) = self.connection_count();
+        connected_count as f64 / total_count as f64
+    }
+
+    pub fn genome_distance(&self, other: &NeuralNetwork) -> Result<GenomeDistance, String> {
+        if self.config() != other.config() {
+            return Err(format!(
+                "can't compare {:?} with {:?}",
+                self.config(),
+                other.config()
+            ));
+        }
+        let mut squared_difference_sum = 0.0;
+        let (mut parameter_count, mut connection_differences, mut connection_count) = (0, 0, 0);
+        let (mut activation_differences, mut node_count) = (0, 0);
+        for (layer, other_layer) in self.layers.iter().zip(other.layers.iter()) {
+            for (a, b) in layer.masked_weights.iter().chain(layer.biases.iter()).zip(
+                other_layer
+                    .masked_weights
+                    .iter()
+                    .chain(other_layer.biases.iter()),
+            ) {
+                squared_difference_sum += (a - b).powi(2);
+                parameter_count += 1;
             }
-            for i in 0..HIDDEN_NODES {
-                match self.connections_hidden_output[i][j] {
-                    true => connected_count += 1,
-                    false => disconnected_count += 1,
-                }
+            connection_differences += layer
+                .connections
+                .iter()
+                .zip(other_layer.connections.iter())
+                .filter(|(a, b)| a != b)
+                .count();
+            connection_count += layer.connections.len();
+            activation_differences += layer
+                .activation_functions
+                .iter()
+                .zip(other_layer.activation_functions.iter())
+                .filter(|(a, b)| a != b)
+                .count();
+            node_count += layer.nodes;
+        }
+        Ok(GenomeDistance {
+            weight: (squared_difference_sum / parameter_count as f64).sqrt(),
+            connection: connection_differences as f64 / connection_count as f64,
+            activation: activation_differences as f64 / node_count as f64,
+        })
+    }
+
+    // Fractions of nodes using sigmoid, leaky relu and tanh
+    pub fn activation_rates(&self) -> [f64; ActivationFunctionType::COUNT] {
+        let mut counts = [0; ActivationFunctionType::COUNT];
+        for layer in self.layers.iter() {
+            for activation_function in layer.activation_functions.iter() {
+                counts[*activation_function as usize] += 1;
             }
         }
-        println!(
-            "Connection Rate: {}",
-            (connected_count as f64) / (connected_count as f64 + disconnected_count as f64)
-        );
-        println!(
-            "Sigmoid Rate: {}",
-            (sigmoid_count as f64)
-                / (sigmoid_count as f64 + leaky_relu_count as f64 + tanh_count as f64)
-        );
-        println!(
-            "Leaky Relu Rate: {}",
-            (leaky_relu_count as f64)
-                / (sigmoid_count as f64 + leaky_relu_count as f64 + tanh_count as f64)
-        );
-        println!(
-            "Tanh Rate: {}",
-            (tanh_count as f64)
-                / (sigmoid_count as f64 + leaky_relu_count as f64 + tanh_count as f64)
-        );
+        let total: usize = counts.iter().sum();
+        counts.map(|count| count as f64 / total as f64)
+    }
+
+    pub fn stats(&self) {
+        let [sigmoid_rate, leaky_relu_rate, tanh_rate] = self.activation_rates();
+        println!("Hidden Layers: {:?}", self.hidden_layers());
+        println!("Recurrent: {}", self.recurrent);
+        println!("Encoding: {:?}", self.encoding);
+        println!("Connection Rate: {}", self.connection_rate());
+        println!("Sigmoid Rate: {}", sigmoid_rate);
+        println!("Leaky Relu Rate: {}", leaky_relu_rate);
+        println!("Tanh Rate: {}", tanh_rate);
+        let node_count: usize = self.layers.iter().map(|layer| layer.nodes).sum();
+        let step_size_sum: f64 = self
+            .layers
+            .iter()
+            .map(|layer| layer.step_sizes.iter().sum::<f64>())
+            .sum();
+        if step_size_sum > 0.0 {
+            println!("Mean Step Size: {}", step_size_sum / node_count as f64);
+        }
+    }
+}
+
+// Standard deviation of each weight and bias across networks of the same shape, averaged over
+// all of them, disabled connections count as zero weights
+pub fn weight_spread(networks: &[&NeuralNetwork]) -> f64 {
+    let mut deviation_sum = 0.0;
+    let mut parameter_count = 0;
+    for l in 0..networks[0].layers.len() {
+        let parameters: Vec<Vec<f64>> = networks
+            .iter()
+            .map(|nn| {
+                let layer = &nn.layers[l];
+                layer
+                    .masked_weights
+                    .iter()
+                    .chain(layer.biases.iter())
+                    .copied()
+                    .collect()
+            })
+            .collect();
+        let count = parameters.len() as f64;
+        for p in 0..parameters[0].len() {
+            let mean = parameters.iter().map(|v| v[p]).sum::<f64>() / count;
+            let variance = parameters
+                .iter()
+                .map(|v| (v[p] - mean).powi(2))
+                .sum::<f64>()
+                / count;
+            deviation_sum += variance.sqrt();
+            parameter_count += 1;
+        }
+    }
+    deviation_sum / parameter_count as f64
+}
+
+impl Gradients {
+    pub fn new(nn: &NeuralNetwork) -> Self {
+        Gradients {
+            weights: nn
+                .layers
+                .iter()
+                .map(|layer| vec![0.0; layer.weights.len()])
+                .collect(),
+            biases: nn
+                .layers
+                .iter()
+                .map(|layer| vec![0.0; layer.nodes])
+                .collect(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for weights in self.weights.iter_mut() {
+            weights.fill(0.0);
+        }
+        for biases in self.biases.iter_mut() {
+            biases.fill(0.0);
+        }
+    }
+}
+
+impl Seat<'_> {
+    pub fn non_finite_decision(&self) -> Option<DecisionRecord> {
+        self.memory.lock().unwrap().non_finite.clone()
+    }
+}
+
+impl Player for Seat<'_> {
+    fn get_action(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex {
+        let mut memory = self.memory.lock().unwrap();
+        let final_outputs = if self.nn.recurrent {
+            let events = encode_events(inputs, memory.previous_inputs.as_ref());
+            memory.previous_inputs = Some(*inputs);
+            self.nn.query(&events, &mut memory.state)
+        } else {
+            self.nn.query_encoded(inputs, &mut memory.state)
+        };
+        let action = self
+            .nn
+            .choose_action(inputs, &final_outputs, available_actions, self.policy);
+        if memory.non_finite.is_none() && !all_finite(&final_outputs, available_actions) {
+            memory.non_finite = Some(DecisionRecord::new(inputs, available_actions, action));
+        }
+        action
+    }
+
+    fn end_round(&self, _own_score_delta: u8, _other_score_delta: u8) {
+        let mut memory = self.memory.lock().unwrap();
+        memory.state = self.nn.initial_state();
+        memory.previous_inputs = None;
     }
 }
@@ -0,0 +1,286 @@
+use std::{collections::BTreeMap, error::Error, fs, str::FromStr};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::euchre::statistics::{fraction, rate, PlayStatistics};
+
+use super::neural_network::NeuralNetwork;
+
+pub const DESCRIPTOR_NAMES: [&str; 3] = ["bid_aggressiveness", "alone_rate", "trump_lead_rate"];
+
+// Where an organism's play falls on each behaviour, all within [0, 1]
+pub type Descriptor = [f64; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SearchMode {
+    // selection by win rate alone
+    #[default]
+    Objective,
+    // selection by win rate blended with novelty, the mean distance from an organism's descriptor
+    // to the k nearest of the rest of the population and the archive. Each organism joins the
+    // archive with archive_probability, and weight is the share of fitness given to novelty
+    Novelty {
+        k: usize,
+        archive_probability: f64,
+        weight: f64,
+    },
+    // MAP-Elites, each descriptor is split into bins and the archive keeps the organism with the
+    // best win rate found in every cell, children are bred from elites drawn from the archive
+    MapElites {
+        bins: usize,
+    },
+}
+
+pub struct Elite {
+    pub nn: NeuralNetwork,
+    pub win_rate: f64,
+    pub descriptor: Descriptor,
+    pub generation: usize,
+}
+
+// Everything a search mode keeps across generations
+pub enum SearchArchive {
+    Objective,
+    Novelty(Vec<Descriptor>),
+    MapElites(BTreeMap<[usize; 3], Elite>),
+}
+
+// Calls per round, the fraction of calls played alone and the fraction of leads that were trump
+pub fn descriptor(statistics: &PlayStatistics) -> Descriptor {
+    [
+        rate(statistics.calls(), statistics.rounds),
+        rate(statistics.alone, statistics.calls()),
+        rate(statistics.trump_leads, statistics.leads),
+    ]
+}
+
+fn distance(a: &Descriptor, b: &Descriptor) -> f64 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+// Mean distance from each descriptor to its k nearest neighbours among the others and the archive
+pub fn novelty_scores(descriptors: &[Descriptor], archive: &[Descriptor], k: usize) -> Vec<f64> {
+    (0..descriptors.len())
+        .map(|i| {
+            let mut distances: Vec<f64> = descriptors
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, other)| other)
+                .chain(archive.iter())
+                .map(|other| distance(&descriptors[i], other))
+                .collect();
+            distances.sort_by(|a, b| a.total_cmp(b));
+            let nearest = &distances[..k.min(distances.len())];
+            if nearest.is_empty() {
+                return 0.0;
+            }
+            nearest.iter().sum::<f64>() / nearest.len() as f64
+        })
+        .collect()
+}
+
+fn cell(descriptor: &Descriptor, bins: usize) -> [usize; 3] {
+    descriptor.map(|value| ((value * bins as f64) as usize).min(bins - 1))
+}
+
+impl FromStr for SearchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowered = s.to_lowercase();
+        let (name, parameter) = match lowered.split_once(':') {
+            Some((name, parameter)) => (name, Some(parameter)),
+            None => (lowered.as_str(), None),
+        };
+        let mode = match name {
+            "objective" => SearchMode::Objective,
+            "novelty" => SearchMode::Novelty {
+                k: 15,
+                archive_probability: 0.05,
+                weight: match parameter {
+                    Some(parameter) => parameter
+                        .parse::<f64>()
+                        .map_err(|_| format!("invalid search parameter {}", parameter))?,
+                    None => 1.0,
+                },
+            },
+            "map_elites" => SearchMode::MapElites {
+                bins: match parameter {
+                    Some(parameter) => parameter
+                        .parse::<usize>()
+                        .map_err(|_| format!("invalid search parameter {}", parameter))?,
+                    None => 5,
+                },
+            },
+            _ => return Err(format!("unknown search mode {}", s)),
+        };
+        mode.validate()?;
+        Ok(mode)
+    }
+}
+
+impl SearchMode {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            SearchMode::Objective => Ok(()),
+            SearchMode::Novelty {
+                k,
+                archive_probability,
+                weight,
+            } => {
+                if k == 0 {
+                    return Err("search.k must be at least 1".to_string());
+                }
+                if !(0.0..=1.0).contains(&archive_probability) {
+                    return Err("search.archive_probability must be within [0, 1]".to_string());
+                }
+                if !(0.0..=1.0).contains(&weight) {
+                    return Err("search.weight must be within [0, 1]".to_string());
+                }
+                Ok(())
+            }
+            SearchMode::MapElites { bins: 0 } => Err("search.bins must be at least 1".to_string()),
+            SearchMode::MapElites { .. } => Ok(()),
+        }
+    }
+}
+
+impl SearchArchive {
+    pub fn new(mode: SearchMode) -> Self {
+        match mode {
+            SearchMode::Objective => SearchArchive::Objective,
+            SearchMode::Novelty { .. } => SearchArchive::Novelty(Vec::new()),
+            SearchMode::MapElites { .. } => SearchArchive::MapElites(BTreeMap::new()),
+        }
+    }
+
+    // Adds the evaluated population to the archive and returns the fitness to select on
+    pub fn update<R: Rng>(
+        &mut self,
+        mode: SearchMode,
+        generation: usize,
+        networks: &[&NeuralNetwork],
+        win_rates: &[f64],
+        descriptors: &[Descriptor],
+        rng: &mut R,
+    ) -> Vec<f64> {
+        match (self, mode) {
+            (
+                SearchArchive::Novelty(archive),
+                SearchMode::Novelty {
+                    k,
+                    archive_probability,
+                    weight,
+                },
+            ) => {
+                let novelty = novelty_scores(descriptors, archive, k);
+                let most_novel = novelty.iter().copied().fold(0.0, f64::max);
+                archive.extend(
+                    descriptors
+                        .iter()
+                        .filter(|_| rng.gen_bool(archive_probability)),
+                );
+                // novelty as a fraction of the most novel organism's, so it weighs the same as a
+                // win rate
+                win_rates
+                    .iter()
+                    .zip(novelty.iter())
+                    .map(|(win_rate, novelty)| {
+                        (1.0 - weight) * win_rate + weight * fraction(*novelty, most_novel)
+                    })
+                    .collect()
+            }
+            (SearchArchive::MapElites(elites), SearchMode::MapElites { bins }) => {
+                for ((nn, &win_rate), descriptor) in networks.iter().zip(win_rates).zip(descriptors)
+                {
+                    let key = cell(descriptor, bins);
+                    if elites
+                        .get(&key)
+                        .is_none_or(|elite| win_rate > elite.win_rate)
+                    {
+                        elites.insert(
+                            key,
+                            Elite {
+                                nn: (*nn).clone(),
+                                win_rate,
+                                descriptor: *descriptor,
+                                generation,
+                            },
+                        );
+                    }
+                }
+                win_rates.to_vec()
+            }
+            _ => win_rates.to_vec(),
+        }
+    }
+
+    // Networks children are bred from instead of the population
+    pub fn elites(&self) -> Option<Vec<&NeuralNetwork>> {
+        match self {
+            SearchArchive::MapElites(elites) => {
+                Some(elites.values().map(|elite| &elite.nn).collect())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn size(&self) -> Option<usize> {
+        match self {
+            SearchArchive::Objective => None,
+            SearchArchive::Novelty(archive) => Some(archive.len()),
+            SearchArchive::MapElites(elites) => Some(elites.len()),
+        }
+    }
+
+    // Writes the novelty archive's descriptors to novelty/archive.csv, or every elite to
+    // map_elites/ along with map_elites/archive.csv
+    pub fn save(&self, out_dir: &str) -> Result<(), Box<dyn Error>> {
+        let header = format!("{}\n", DESCRIPTOR_NAMES.join(","));
+        match self {
+            SearchArchive::Objective => (),
+            SearchArchive::Novelty(archive) => {
+                println!("Saving Novelty Archive");
+                fs::create_dir_all(format!("{}/novelty", out_dir))?;
+                let mut csv = header;
+                for descriptor in archive.iter() {
+                    csv.push_str(&format!(
+                        "{},{},{}\n",
+                        descriptor[0], descriptor[1], descriptor[2]
+                    ));
+                }
+                fs::write(format!("{}/novelty/archive.csv", out_dir), csv)?;
+            }
+            SearchArchive::MapElites(elites) => {
+                println!("Saving MAP-Elites Archive");
+                let elites_dir = format!("{}/map_elites", out_dir);
+                fs::create_dir_all(&elites_dir)?;
+                let mut csv = format!("file,win_rate,generation,{}", header);
+                for (key, elite) in elites.iter() {
+                    let filename = format!("cell({}-{}-{}).bin", key[0], key[1], key[2]);
+                    elite
+                        .nn
+                        .save_to_file(format!("{}/{}", elites_dir, filename).as_str())?;
+                    csv.push_str(&format!(
+                        "{},{},{},{},{},{}\n",
+                        filename,
+                        elite.win_rate,
+                        elite.generation,
+                        elite.descriptor[0],
+                        elite.descriptor[1],
+                        elite.descriptor[2]
+                    ));
+                }
+                fs::write(format!("{}/archive.csv", elites_dir), csv)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::neural_network::NeuralNetwork;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComplexityObjective {
+    // fraction of possible connections that are disabled
+    Sparsity,
+    // number of enabled connections, fewer is better
+    Size,
+}
+
+impl ComplexityObjective {
+    // Larger is better, to match win rate
+    pub fn value(&self, nn: &NeuralNetwork) -> f64 {
+        match self {
+            ComplexityObjective::Sparsity => 1.0 - nn.connection_rate(),
+            ComplexityObjective::Size => -(nn.connection_count().0 as f64),
+        }
+    }
+}
+
+// a dominates b if it is at least as good on every objective and better on one, all maximised
+fn dominates(a: &[f64], b: &[f64]) -> bool {
+    let mut better = false;
+    for (x, y) in a.iter().zip(b.iter()) {
+        if x < y {
+            return false;
+        }
+        if x > y {
+            better = true;
+        }
+    }
+    better
+}
+
+// Fast non-dominated sort, fronts[0] is the Pareto front
+pub fn non_dominated_fronts(objectives: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    let n = objectives.len();
+    let mut dominated_by: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut domination_counts: Vec<usize> = vec![0; n];
+    let mut fronts: Vec<Vec<usize>> = vec![Vec::new()];
+    for p in 0..n {
+        for q in 0..n {
+            if dominates(&objectives[p], &objectives[q]) {
+                dominated_by[p].push(q);
+            } else if dominates(&objectives[q], &objectives[p]) {
+                domination_counts[p] += 1;
+            }
+        }
+        if domination_counts[p] == 0 {
+            fronts[0].push(p);
+        }
+    }
+    let mut current = 0;
+    while !fronts[current].is_empty() {
+        let mut next_front = Vec::new();
+        for &p in fronts[current].iter() {
+            for &q in dominated_by[p].iter() {
+                domination_counts[q] -= 1;
+                if domination_counts[q] == 0 {
+                    next_front.push(q);
+                }
+            }
+        }
+        current += 1;
+        fronts.push(next_front);
+    }
+    fronts.pop();
+    fronts
+}
+
+fn crowding_distances(objectives: &[Vec<f64>], front: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; front.len()];
+    if front.is_empty() {
+        return distances;
+    }
+    // each objective's values across the front
+    let columns = (0..objectives[front[0]].len()).map(|m| {
+        front
+            .iter()
+            .map(|&i| objectives[i][m])
+            .collect::<Vec<f64>>()
+    });
+    for values in columns {
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+        let min = values[order[0]];
+        let max = values[order[order.len() - 1]];
+        distances[order[0]] = f64::INFINITY;
+        distances[order[order.len() - 1]] = f64::INFINITY;
+        if max - min <= 0.0 {
+            continue;
+        }
+        for k in 1..order.len().saturating_sub(1) {
+            distances[order[k]] += (values[order[k + 1]] - values[order[k - 1]]) / (max - min);
+        }
+    }
+    distances
+}
+
+// Indices ordered by front, then by descending crowding distance within a front, with ties
+// broken at random, so earlier is better under the crowded comparison operator
+pub fn rank_by_pareto<R: Rng>(objectives: &[Vec<f64>], rng: &mut R) -> Vec<usize> {
+    let mut ranking = Vec::with_capacity(objectives.len());
+    for front in non_dominated_fronts(objectives) {
+        let distances = crowding_distances(objectives, &front);
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.shuffle(rng);
+        order.sort_by(|&a, &b| distances[b].total_cmp(&distances[a]));
+        ranking.extend(order.into_iter().map(|i| front[i]));
+    }
+    ranking
+}
@@ -0,0 +1,240 @@
+use std::str::FromStr;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use strum::EnumCount;
+
+use crate::euchre::enums::ActionIndex;
+
+use super::neural_network::AvailableActions;
+
+// How a seat turns the network's outputs into an action
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActionPolicy {
+    // always the highest scoring available action
+    #[default]
+    Argmax,
+    // sampled in proportion to exp(output / temperature) over the available actions, lower
+    // temperatures get closer to argmax
+    Softmax {
+        temperature: f64,
+    },
+    // a uniformly random available action with probability epsilon, otherwise argmax
+    EpsilonGreedy {
+        epsilon: f64,
+    },
+}
+
+// argmax, softmax:<temperature> or epsilon_greedy:<epsilon>
+impl FromStr for ActionPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowered = s.to_lowercase();
+        let (name, parameter) = match lowered.split_once(':') {
+            Some((name, parameter)) => (name, Some(parameter)),
+            None => (lowered.as_str(), None),
+        };
+        let parameter = |default: f64| match parameter {
+            Some(parameter) => parameter
+                .parse::<f64>()
+                .map_err(|_| format!("invalid policy parameter {}", parameter)),
+            None => Ok(default),
+        };
+        let policy = match name {
+            "argmax" => ActionPolicy::Argmax,
+            "softmax" => ActionPolicy::Softmax {
+                temperature: parameter(1.0)?,
+            },
+            "epsilon_greedy" => ActionPolicy::EpsilonGreedy {
+                epsilon: parameter(0.1)?,
+            },
+            _ => return Err(format!("unknown policy {}", s)),
+        };
+        policy.validate()?;
+        Ok(policy)
+    }
+}
+
+impl ActionPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            ActionPolicy::Softmax { temperature } if temperature <= 0.0 => {
+                Err("policy.temperature must be positive".to_string())
+            }
+            ActionPolicy::EpsilonGreedy { epsilon } if !(0.0..=1.0).contains(&epsilon) => {
+                Err("policy.epsilon must be within [0, 1]".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Given the caller's greedy choice among the available actions
+    pub fn choose<R: Rng>(
+        &self,
+        outputs: &[f64; ActionIndex::COUNT],
+        available_actions: &AvailableActions,
+        greedy: usize,
+        rng: &mut R,
+    ) -> usize {
+        match *self {
+            ActionPolicy::Argmax => greedy,
+            ActionPolicy::Softmax { temperature } => sample(
+                &masked_softmax(outputs, available_actions, temperature),
+                available_actions,
+                rng,
+            ),
+            ActionPolicy::EpsilonGreedy { epsilon } => {
+                if rng.gen::<f64>() >= epsilon {
+                    return greedy;
+                }
+                let available: Vec<usize> = (0..ActionIndex::COUNT)
+                    .filter(|&i| available_actions[i])
+                    .collect();
+                available[rng.gen_range(0..available.len())]
+            }
+        }
+    }
+}
+
+// The first highest finite output among the available actions, or the first available action if
+// none are finite
+pub fn masked_argmax(
+    outputs: &[f64; ActionIndex::COUNT],
+    available_actions: &AvailableActions,
+) -> usize {
+    let mut best: Option<usize> = None;
+    for i in 0..ActionIndex::COUNT {
+        if available_actions[i]
+            && outputs[i].is_finite()
+            && best.is_none_or(|b| outputs[i] > outputs[b])
+        {
+            best = Some(i);
+        }
+    }
+    best.or_else(|| (0..ActionIndex::COUNT).find(|&i| available_actions[i]))
+        .expect("No available actions!")
+}
+
+pub fn all_finite(
+    outputs: &[f64; ActionIndex::COUNT],
+    available_actions: &AvailableActions,
+) -> bool {
+    (0..ActionIndex::COUNT).all(|i| !available_actions[i] || outputs[i].is_finite())
+}
+
+// Softmax of outputs / temperature over the available actions, zero elsewhere. The largest
+// available output is subtracted first so negative and large activations alike stay finite and
+// the best action always gets a positive share
+pub fn masked_softmax(
+    outputs: &[f64; ActionIndex::COUNT],
+    available_actions: &AvailableActions,
+    temperature: f64,
+) -> [f64; ActionIndex::COUNT] {
+    let mut max_output = f64::NEG_INFINITY;
+    for i in 0..ActionIndex::COUNT {
+        if available_actions[i] {
+            max_output = max_output.max(outputs[i]);
+        }
+    }
+    let mut probabilities = [0.0; ActionIndex::COUNT];
+    let mut total = 0.0;
+    for i in 0..ActionIndex::COUNT {
+        if available_actions[i] {
+            probabilities[i] = ((outputs[i] - max_output) / temperature).exp();
+            total += probabilities[i];
+        }
+    }
+    for probability in probabilities.iter_mut() {
+        *probability /= total;
+    }
+    probabilities
+}
+
+// An available action drawn with the given probabilities, the last available action absorbs any
+// rounding left over
+pub fn sample<R: Rng>(
+    probabilities: &[f64; ActionIndex::COUNT],
+    available_actions: &AvailableActions,
+    rng: &mut R,
+) -> usize {
+    let mut target = rng.gen::<f64>();
+    let mut action = ActionIndex::COUNT;
+    for i in 0..ActionIndex::COUNT {
+        if available_actions[i] {
+            action = i;
+            if target < probabilities[i] {
+                break;
+            }
+            target -= probabilities[i];
+        }
+    }
+    action
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn available(actions: &[usize]) -> AvailableActions {
+        let mut available_actions = [false; ActionIndex::COUNT];
+        for &action in actions {
+            available_actions[action] = true;
+        }
+        available_actions
+    }
+
+    #[test]
+    fn masked_argmax_ignores_unavailable_actions() {
+        let mut outputs = [0.0; ActionIndex::COUNT];
+        outputs[3] = 5.0;
+        outputs[7] = 1.0;
+        outputs[9] = 2.0;
+        assert_eq!(masked_argmax(&outputs, &available(&[7, 9])), 9);
+        assert_eq!(masked_argmax(&outputs, &available(&[3, 7, 9])), 3);
+    }
+
+    #[test]
+    fn masked_argmax_takes_the_first_of_a_tie() {
+        let outputs = [1.0; ActionIndex::COUNT];
+        assert_eq!(masked_argmax(&outputs, &available(&[4, 2, 8])), 2);
+    }
+
+    #[test]
+    fn masked_argmax_skips_non_finite_outputs() {
+        let mut outputs = [0.0; ActionIndex::COUNT];
+        outputs[1] = f64::NAN;
+        outputs[2] = f64::INFINITY;
+        outputs[5] = -1.0;
+        assert_eq!(masked_argmax(&outputs, &available(&[1, 2, 5])), 5);
+        // with nothing finite the first available action is taken
+        assert_eq!(masked_argmax(&outputs, &available(&[1, 2])), 1);
+    }
+
+    #[test]
+    fn masked_softmax_is_zero_outside_the_mask_and_sums_to_one() {
+        let mut outputs = [0.0; ActionIndex::COUNT];
+        outputs[0] = 2.0;
+        outputs[1] = 1.0;
+        outputs[2] = 100.0;
+        let probabilities = masked_softmax(&outputs, &available(&[0, 1]), 1.0);
+        assert_eq!(probabilities[2], 0.0);
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!((probabilities[0] / probabilities[1] - 1f64.exp()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn masked_softmax_stays_finite_for_large_and_negative_outputs() {
+        let mut outputs = [0.0; ActionIndex::COUNT];
+        outputs[0] = 1000.0;
+        outputs[1] = 999.0;
+        outputs[2] = -1000.0;
+        let probabilities = masked_softmax(&outputs, &available(&[0, 1, 2]), 0.5);
+        assert!(probabilities.iter().all(|p| p.is_finite()));
+        assert!(probabilities[0] > probabilities[1] && probabilities[1] > probabilities[2]);
+        // lower temperatures sharpen the distribution
+        let sharper = masked_softmax(&outputs, &available(&[0, 1, 2]), 0.1);
+        assert!(sharper[0] > probabilities[0]);
+    }
+}
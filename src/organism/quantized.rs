@@ -0,0 +1,405 @@
+use std::fs::File;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use strum::EnumCount;
+
+use crate::euchre::enums::ActionIndex;
+use crate::euchre::player::Player;
+
+use super::encoding::InputEncoding;
+use super::neural_network::{
+    ActivationFunctionType, AvailableActions, ByteReader, NeuralNetwork, NeuralNetworkInput,
+};
+use super::policy::{all_finite, masked_argmax, ActionPolicy};
+use super::training::DecisionRecord;
+
+const FILE_MAGIC: &[u8; 8] = b"EUCHRENQ";
+const FILE_VERSION: u32 = 1;
+const INT8_MAX: f32 = 127.0;
+
+// Precision of an exported copy, networks are evolved and trained in f64
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Precision {
+    F32,
+    // symmetric per node, scaled so the largest weight into a node is 127
+    Int8,
+}
+
+enum Weights {
+    F32(Vec<f32>),
+    Int8 { values: Vec<i8>, scales: Vec<f32> },
+}
+
+// Weights are indexed by input * nodes + node like the network's, with disabled connections
+// stored as zero
+struct QuantizedLayer {
+    inputs: usize,
+    nodes: usize,
+    weights: Weights,
+    biases: Vec<f32>,
+    activation_functions: Vec<ActivationFunctionType>,
+}
+
+// An inference only copy of a network at lower precision, without the genome needed to evolve or
+// train it
+pub struct QuantizedNetwork {
+    encoding: InputEncoding,
+    precision: Precision,
+    layers: Vec<QuantizedLayer>,
+}
+
+pub struct QuantizedSeat<'a> {
+    nn: &'a QuantizedNetwork,
+    policy: ActionPolicy,
+    // the first decision where an available action's output was NaN or infinite
+    non_finite: Mutex<Option<DecisionRecord>>,
+}
+
+impl FromStr for Precision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "f32" => Ok(Precision::F32),
+            "int8" => Ok(Precision::Int8),
+            _ => Err(format!("unknown precision {}", s)),
+        }
+    }
+}
+
+impl QuantizedLayer {
+    fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        let mut sums = vec![0.0; self.nodes];
+        for (i, &input) in inputs[..self.inputs].iter().enumerate() {
+            if input == 0.0 {
+                continue;
+            }
+            let row = i * self.nodes..(i + 1) * self.nodes;
+            match &self.weights {
+                Weights::F32(weights) => {
+                    for (sum, weight) in sums.iter_mut().zip(&weights[row]) {
+                        *sum += input * weight;
+                    }
+                }
+                Weights::Int8 { values, .. } => {
+                    for (sum, value) in sums.iter_mut().zip(&values[row]) {
+                        *sum += input * *value as f32;
+                    }
+                }
+            }
+        }
+        if let Weights::Int8 { scales, .. } = &self.weights {
+            for (sum, scale) in sums.iter_mut().zip(scales.iter()) {
+                *sum *= scale;
+            }
+        }
+        sums.iter()
+            .zip(self.biases.iter())
+            .zip(self.activation_functions.iter())
+            .map(|((&sum, &bias), &activation_function)| {
+                NeuralNetwork::activate(activation_function, (sum + bias) as f64) as f32
+            })
+            .collect()
+    }
+}
+
+impl QuantizedNetwork {
+    pub fn from_network(nn: &NeuralNetwork, precision: Precision) -> Result<Self, String> {
+        if nn.is_recurrent() {
+            return Err("recurrent networks can't be quantised".to_string());
+        }
+        let layers = nn
+            .layers()
+            .iter()
+            .map(|layer| {
+                let weights = match precision {
+                    Precision::F32 => {
+                        Weights::F32(layer.masked_weights.iter().map(|&w| w as f32).collect())
+                    }
+                    Precision::Int8 => {
+                        let mut scales = vec![0.0f32; layer.nodes];
+                        for (k, weight) in layer.masked_weights.iter().enumerate() {
+                            let j = k % layer.nodes;
+                            scales[j] = scales[j].max(weight.abs() as f32 / INT8_MAX);
+                        }
+                        let values = layer
+                            .masked_weights
+                            .iter()
+                            .enumerate()
+                            .map(|(k, &weight)| {
+                                let scale = scales[k % layer.nodes];
+                                if scale == 0.0 {
+                                    return 0;
+                                }
+                                (weight as f32 / scale).round().clamp(-INT8_MAX, INT8_MAX) as i8
+                            })
+                            .collect();
+                        Weights::Int8 { values, scales }
+                    }
+                };
+                QuantizedLayer {
+                    inputs: layer.inputs,
+                    nodes: layer.nodes,
+                    weights,
+                    biases: layer.biases.iter().map(|&b| b as f32).collect(),
+                    activation_functions: layer.activation_functions.clone(),
+                }
+            })
+            .collect();
+        Ok(QuantizedNetwork {
+            encoding: nn.encoding(),
+            precision,
+            layers,
+        })
+    }
+
+    pub fn seat_with_policy(&self, policy: ActionPolicy) -> QuantizedSeat<'_> {
+        QuantizedSeat {
+            nn: self,
+            policy,
+            non_finite: Mutex::new(None),
+        }
+    }
+
+    pub fn precision(&self) -> Precision {
+        self.precision
+    }
+
+    pub fn outputs(&self, inputs: &NeuralNetworkInput) -> [f64; ActionIndex::COUNT] {
+        let (encoded, actions) = self.encoding.encode(inputs);
+        let mut outputs: Vec<f32> = encoded.iter().map(|&x| x as f32).collect();
+        for layer in self.layers.iter() {
+            outputs = layer.forward(&outputs);
+        }
+        let mut final_outputs = [0.0; ActionIndex::COUNT];
+        for (output, &action) in outputs.iter().zip(actions.iter()) {
+            final_outputs[action] = *output as f64;
+        }
+        final_outputs
+    }
+
+    // Little endian, magic and version, input encoding, precision, input count, layer count and
+    // widths, then each layer's weights (with a scale per node for int8), biases and activation
+    // functions
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(FILE_MAGIC);
+        bytes.extend_from_slice(&FILE_VERSION.to_le_bytes());
+        bytes.push(self.encoding as u8);
+        bytes.push(self.precision as u8);
+        bytes.extend_from_slice(&(self.layers[0].inputs as u32).to_le_bytes());
+        bytes.extend_from_slice(&(self.layers.len() as u32).to_le_bytes());
+        for layer in self.layers.iter() {
+            bytes.extend_from_slice(&(layer.nodes as u32).to_le_bytes());
+        }
+        for layer in self.layers.iter() {
+            match &layer.weights {
+                Weights::F32(weights) => {
+                    for weight in weights.iter() {
+                        bytes.extend_from_slice(&weight.to_le_bytes());
+                    }
+                }
+                Weights::Int8 { values, scales } => {
+                    for value in values.iter() {
+                        bytes.push(*value as u8);
+                    }
+                    for scale in scales.iter() {
+                        bytes.extend_from_slice(&scale.to_le_bytes());
+                    }
+                }
+            }
+            for bias in layer.biases.iter() {
+                bytes.extend_from_slice(&bias.to_le_bytes());
+            }
+            for activation_function in layer.activation_functions.iter() {
+                bytes.push(*activation_function as u8);
+            }
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> std::io::Result<QuantizedNetwork> {
+        if !bytes.starts_with(FILE_MAGIC) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "not a quantised network file",
+            ));
+        }
+        let mut reader = ByteReader {
+            bytes,
+            position: FILE_MAGIC.len(),
+        };
+        let version = reader.read_u32()?;
+        if version == 0 || version > FILE_VERSION {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("unsupported quantised network file version {}", version),
+            ));
+        }
+        let encoding = InputEncoding::from_u8(reader.read_u8()?)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "unknown input encoding"))?;
+        let precision = match reader.read_u8()? {
+            0 => Precision::F32,
+            1 => Precision::Int8,
+            value => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unknown precision {}", value),
+                ))
+            }
+        };
+        let input_count = reader.read_u32()? as usize;
+        if input_count != encoding.input_count() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "network has {} inputs, expected {}",
+                    input_count,
+                    encoding.input_count()
+                ),
+            ));
+        }
+        let layer_count = reader.read_u32()? as usize;
+        let mut widths = Vec::with_capacity(layer_count);
+        for _ in 0..layer_count {
+            widths.push(reader.read_u32()? as usize);
+        }
+        if widths.last() != Some(&ActionIndex::COUNT) || widths.contains(&0) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("invalid layer widths {:?}", widths),
+            ));
+        }
+        let mut layers = Vec::with_capacity(layer_count);
+        let mut inputs = input_count;
+        for nodes in widths {
+            let weights = match precision {
+                Precision::F32 => {
+                    let mut weights = Vec::with_capacity(inputs * nodes);
+                    for _ in 0..inputs * nodes {
+                        weights.push(reader.read_f32()?);
+                    }
+                    Weights::F32(weights)
+                }
+                Precision::Int8 => {
+                    let mut values = Vec::with_capacity(inputs * nodes);
+                    for _ in 0..inputs * nodes {
+                        values.push(reader.read_u8()? as i8);
+                    }
+                    let mut scales = Vec::with_capacity(nodes);
+                    for _ in 0..nodes {
+                        scales.push(reader.read_f32()?);
+                    }
+                    Weights::Int8 { values, scales }
+                }
+            };
+            let mut biases = Vec::with_capacity(nodes);
+            for _ in 0..nodes {
+                biases.push(reader.read_f32()?);
+            }
+            let mut activation_functions = Vec::with_capacity(nodes);
+            for _ in 0..nodes {
+                activation_functions.push(reader.read_activation_function()?);
+            }
+            layers.push(QuantizedLayer {
+                inputs,
+                nodes,
+                weights,
+                biases,
+                activation_functions,
+            });
+            inputs = nodes;
+        }
+        Ok(QuantizedNetwork {
+            encoding,
+            precision,
+            layers,
+        })
+    }
+
+    pub fn save_to_file(&self, filename: &str) -> std::io::Result<()> {
+        let mut file = File::create(filename)?;
+        file.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load_from_file(filename: &str) -> std::io::Result<QuantizedNetwork> {
+        let mut file = File::open(filename)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        QuantizedNetwork::from_bytes(&bytes)
+    }
+
+    pub fn is_quantized_file(filename: &str) -> std::io::Result<bool> {
+        let mut file = File::open(filename)?;
+        let mut magic = [0; FILE_MAGIC.len()];
+        match file.read_exact(&mut magic) {
+            Ok(()) => Ok(&magic == FILE_MAGIC),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl QuantizedSeat<'_> {
+    pub fn non_finite_decision(&self) -> Option<DecisionRecord> {
+        self.non_finite.lock().unwrap().clone()
+    }
+}
+
+impl Player for QuantizedSeat<'_> {
+    fn get_action(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex {
+        let final_outputs = self.nn.outputs(inputs);
+        let greedy = masked_argmax(&final_outputs, available_actions);
+        if !all_finite(&final_outputs, available_actions) {
+            let action = ActionIndex::from_usize(greedy);
+            let mut non_finite = self.non_finite.lock().unwrap();
+            if non_finite.is_none() {
+                *non_finite = Some(DecisionRecord::new(inputs, available_actions, action));
+            }
+            return action;
+        }
+        ActionIndex::from_usize(self.policy.choose(
+            &final_outputs,
+            available_actions,
+            greedy,
+            &mut rand::thread_rng(),
+        ))
+    }
+}
+
+// Fraction of the decisions where the quantised network picks a different action from the
+// original, and the largest difference between their outputs
+pub fn compare_quantized(
+    nn: &NeuralNetwork,
+    quantized: &QuantizedNetwork,
+    records: &[DecisionRecord],
+) -> (f64, f64) {
+    let seat = nn.seat();
+    let quantized_seat = quantized.seat_with_policy(ActionPolicy::Argmax);
+    let mut different = 0;
+    let mut max_difference: f64 = 0.0;
+    for record in records {
+        let inputs = record.inputs();
+        let available_actions = record.available_actions();
+        if seat.get_action(&inputs, &available_actions) as usize
+            != quantized_seat.get_action(&inputs, &available_actions) as usize
+        {
+            different += 1;
+        }
+        let (original, lowered) = (nn.outputs(&inputs), quantized.outputs(&inputs));
+        for k in 0..ActionIndex::COUNT {
+            max_difference = max_difference.max((original[k] - lowered[k]).abs());
+        }
+    }
+    (
+        different as f64 / records.len().max(1) as f64,
+        max_difference,
+    )
+}
@@ -0,0 +1,213 @@
+use std::{str::FromStr, sync::Mutex};
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::euchre::{
+    enums::{ActionIndex, Team},
+    game::play_euchre,
+    player::Player,
+};
+
+use super::neural_network::{AvailableActions, Gradients, NeuralNetwork, NeuralNetworkInput};
+use super::policy::sample;
+use super::training::DecisionRecord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardMode {
+    // every decision is rewarded +1 if the seat's team won the game, -1 otherwise
+    Game,
+    // every decision is rewarded with the points its team gained minus those conceded that round
+    Round,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReinforcementConfig {
+    pub games: usize,
+    pub games_per_update: usize,
+    pub learning_rate: f64,
+    // softmax temperature of the sampled policy
+    pub temperature: f64,
+    pub reward: RewardMode,
+    pub thread_count: usize,
+}
+
+// A seat in a self-play game, sampling actions from the network and keeping its trajectory
+struct TrajectoryPlayer<'a> {
+    nn: &'a NeuralNetwork,
+    temperature: f64,
+    decisions: Mutex<Vec<DecisionRecord>>,
+    // (decisions made by the end of the round, points for, points against)
+    rounds: Mutex<Vec<(usize, u8, u8)>>,
+}
+
+impl FromStr for RewardMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "game" => Ok(RewardMode::Game),
+            "round" => Ok(RewardMode::Round),
+            _ => Err(format!("unknown reward mode {}", s)),
+        }
+    }
+}
+
+impl Default for ReinforcementConfig {
+    fn default() -> Self {
+        ReinforcementConfig {
+            games: 1000,
+            games_per_update: 10,
+            learning_rate: 0.01,
+            temperature: 0.1,
+            reward: RewardMode::Game,
+            thread_count: 1,
+        }
+    }
+}
+
+impl ReinforcementConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.games == 0 || self.games_per_update == 0 {
+            return Err("games and games_per_update must be at least 1".to_string());
+        }
+        if self.learning_rate <= 0.0 {
+            return Err("learning_rate must be positive".to_string());
+        }
+        if self.temperature <= 0.0 {
+            return Err("temperature must be positive".to_string());
+        }
+        if self.thread_count == 0 {
+            return Err("thread_count must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl<'a> TrajectoryPlayer<'a> {
+    fn new(nn: &'a NeuralNetwork, temperature: f64) -> Self {
+        TrajectoryPlayer {
+            nn,
+            temperature,
+            decisions: Mutex::new(Vec::new()),
+            rounds: Mutex::new(Vec::new()),
+        }
+    }
+
+    // Decisions paired with their rewards
+    fn into_trajectory(self, won: bool, reward: RewardMode) -> Vec<(DecisionRecord, f64)> {
+        let decisions = self.decisions.into_inner().unwrap();
+        let rounds = self.rounds.into_inner().unwrap();
+        let mut rewards = vec![0.0; decisions.len()];
+        match reward {
+            RewardMode::Game => rewards.fill(if won { 1.0 } else { -1.0 }),
+            RewardMode::Round => {
+                let mut start = 0;
+                for (end, own, other) in rounds {
+                    rewards[start..end].fill(own as f64 - other as f64);
+                    start = end;
+                }
+            }
+        }
+        decisions.into_iter().zip(rewards).collect()
+    }
+}
+
+impl Player for TrajectoryPlayer<'_> {
+    fn get_action(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex {
+        let probabilities =
+            self.nn
+                .action_probabilities(inputs, available_actions, self.temperature);
+        let action = ActionIndex::from_usize(sample(
+            &probabilities,
+            available_actions,
+            &mut rand::thread_rng(),
+        ));
+        self.decisions
+            .lock()
+            .unwrap()
+            .push(DecisionRecord::new(inputs, available_actions, action));
+        action
+    }
+
+    fn end_round(&self, own_score_delta: u8, other_score_delta: u8) {
+        let decision_count = self.decisions.lock().unwrap().len();
+        self.rounds
+            .lock()
+            .unwrap()
+            .push((decision_count, own_score_delta, other_score_delta));
+    }
+}
+
+// One self-play game with the network in every seat
+fn play_self_play_game(
+    nn: &NeuralNetwork,
+    config: &ReinforcementConfig,
+) -> Vec<(DecisionRecord, f64)> {
+    let seats: Vec<TrajectoryPlayer> = (0..4)
+        .map(|_| TrajectoryPlayer::new(nn, config.temperature))
+        .collect();
+    let winner = play_euchre(&seats[0], &seats[1], &seats[2], &seats[3]);
+    let mut trajectory = Vec::new();
+    for (seat, player) in seats.into_iter().enumerate() {
+        let won = match winner {
+            Team::NorthSouth => seat % 2 == 0,
+            Team::EastWest => seat % 2 == 1,
+        };
+        trajectory.append(&mut player.into_trajectory(won, config.reward));
+    }
+    trajectory
+}
+
+// REINFORCE on self-play games, using the mean reward of each update's decisions as the baseline
+pub fn train_reinforcement(nn: &mut NeuralNetwork, config: &ReinforcementConfig) {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.thread_count)
+        .build()
+        .unwrap();
+    let updates = config.games.div_ceil(config.games_per_update);
+    let mut gradients = Gradients::new(nn);
+    for update in 1..updates + 1 {
+        let games = config
+            .games_per_update
+            .min(config.games - (update - 1) * config.games_per_update);
+        let network: &NeuralNetwork = nn;
+        let trajectory: Vec<(DecisionRecord, f64)> = pool.install(|| {
+            (0..games)
+                .into_par_iter()
+                .flat_map(|_| play_self_play_game(network, config))
+                .collect()
+        });
+        let baseline =
+            trajectory.iter().map(|(_, reward)| reward).sum::<f64>() / trajectory.len() as f64;
+        let mut total_loss = 0.0;
+        for (decision, reward) in trajectory.iter() {
+            let (loss, _) = network.backpropagate(
+                &decision.inputs(),
+                &decision.available_actions(),
+                ActionIndex::from_usize(decision.action),
+                config.temperature,
+                reward - baseline,
+                &mut gradients,
+            );
+            total_loss += loss;
+        }
+        nn.apply_gradients(
+            &mut gradients,
+            config.learning_rate / trajectory.len() as f64,
+        );
+        println!(
+            "Update {}/{} - Decisions: {}, Mean Reward: {}, Mean Cross Entropy: {}",
+            update,
+            updates,
+            trajectory.len(),
+            baseline,
+            total_loss / trajectory.len() as f64
+        );
+    }
+}
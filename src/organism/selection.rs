@@ -0,0 +1,154 @@
+use rand::{
+    seq::{index::sample, SliceRandom},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
+
+use super::pareto::ComplexityObjective;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SelectionScheme {
+    // parents drawn uniformly from the fittest breeding_fraction, survivor_fraction kept as elites
+    Truncation,
+    // each parent is the fittest of size organisms drawn from the whole population
+    Tournament { size: usize },
+    // linear ranking over the whole population, pressure in [1, 2] is the expected
+    // number of offspring of the fittest organism
+    Rank { pressure: f64 },
+    // the fittest breeding_fraction breed and survive to compete with their children
+    MuPlusLambda,
+    // the fittest breeding_fraction breed and are all replaced by their children
+    MuCommaLambda,
+    // NSGA-II, win rate and the complexity objective are traded off, the ranking is by Pareto
+    // front then crowding distance, and parents are the better of two from the breeding pool
+    Nsga2 { objective: ComplexityObjective },
+}
+
+// Indices of the population ordered from fittest to least fit, ties broken at random
+pub fn rank_by_fitness<R: Rng>(fitness: &[f64], rng: &mut R) -> Vec<usize> {
+    let mut ranking: Vec<usize> = (0..fitness.len()).collect();
+    ranking.shuffle(rng);
+    ranking.sort_by(|&a, &b| fitness[b].total_cmp(&fitness[a]));
+    ranking
+}
+
+impl SelectionScheme {
+    pub fn survivor_count(
+        &self,
+        population_size: usize,
+        survivor_fraction: f64,
+        breeder_count: usize,
+    ) -> usize {
+        match self {
+            SelectionScheme::MuPlusLambda => breeder_count,
+            SelectionScheme::MuCommaLambda => 0,
+            _ => (population_size as f64 * survivor_fraction).round() as usize,
+        }
+    }
+
+    pub fn uses_breeding_pool(&self) -> bool {
+        matches!(
+            self,
+            SelectionScheme::Truncation
+                | SelectionScheme::MuPlusLambda
+                | SelectionScheme::MuCommaLambda
+                | SelectionScheme::Nsga2 { .. }
+        )
+    }
+
+    pub fn validate(&self, population_size: usize) -> Result<(), String> {
+        match *self {
+            SelectionScheme::Tournament { size } if size == 0 || size > population_size => {
+                Err(format!(
+                    "selection.scheme.size must be within [1, {}]",
+                    population_size
+                ))
+            }
+            SelectionScheme::Rank { pressure } if !(1.0..=2.0).contains(&pressure) => {
+                Err("selection.scheme.pressure must be within [1, 2]".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Picks two distinct parents per child, returned as indices into the population
+    pub fn select_parents<R: Rng>(
+        &self,
+        fitness: &[f64],
+        ranking: &[usize],
+        breeder_count: usize,
+        child_count: usize,
+        rng: &mut R,
+    ) -> Vec<(usize, usize)> {
+        let mut parent_matchings = Vec::with_capacity(child_count);
+        for _ in 0..child_count {
+            let first = self.select_parent(fitness, ranking, breeder_count, None, rng);
+            let second = self.select_parent(fitness, ranking, breeder_count, Some(first), rng);
+            parent_matchings.push((first, second));
+        }
+        parent_matchings
+    }
+
+    fn select_parent<R: Rng>(
+        &self,
+        fitness: &[f64],
+        ranking: &[usize],
+        breeder_count: usize,
+        exclude: Option<usize>,
+        rng: &mut R,
+    ) -> usize {
+        let candidates: Vec<usize> = match self {
+            SelectionScheme::Truncation
+            | SelectionScheme::MuPlusLambda
+            | SelectionScheme::MuCommaLambda
+            | SelectionScheme::Nsga2 { .. } => ranking[..breeder_count].to_vec(),
+            _ => ranking.to_vec(),
+        }
+        .into_iter()
+        .filter(|&i| Some(i) != exclude)
+        .collect();
+        match *self {
+            SelectionScheme::Truncation
+            | SelectionScheme::MuPlusLambda
+            | SelectionScheme::MuCommaLambda => *candidates.choose(rng).unwrap(),
+            SelectionScheme::Tournament { size } => *candidates
+                .choose_multiple(rng, size.min(candidates.len()))
+                .max_by(|&&a, &&b| fitness[a].total_cmp(&fitness[b]))
+                .unwrap(),
+            SelectionScheme::Nsga2 { .. } => {
+                // candidates are in crowded comparison order, so the earlier of the two wins
+                *sample(rng, candidates.len(), 2.min(candidates.len()))
+                    .iter()
+                    .min()
+                    .map(|position| &candidates[position])
+                    .unwrap()
+            }
+            SelectionScheme::Rank { pressure } => {
+                // candidates are fittest first, so the weight falls linearly with position
+                let n = candidates.len() as f64;
+                let position = if n > 1.0 {
+                    let weights: Vec<f64> = (0..candidates.len())
+                        .map(|i| {
+                            (2.0 - pressure) / n
+                                + 2.0 * (n - 1.0 - i as f64) * (pressure - 1.0) / (n * (n - 1.0))
+                        })
+                        .collect();
+                    let mut target = rng.gen::<f64>() * weights.iter().sum::<f64>();
+                    let mut position = candidates.len() - 1;
+                    for (i, weight) in weights.iter().enumerate() {
+                        if target < *weight {
+                            position = i;
+                            break;
+                        }
+                        target -= weight;
+                    }
+                    position
+                } else {
+                    0
+                };
+                candidates[position]
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::str::FromStr;
+
+use rand::Rng;
+
+// z for a two sided 95% interval
+const Z_95: f64 = 1.959964;
+const BOOTSTRAP_SAMPLES: usize = 2000;
+const BRADLEY_TERRY_ITERATIONS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntervalMethod {
+    Wilson,
+    // percentile interval of the win rate over games resampled with replacement
+    Bootstrap,
+}
+
+// Sequential probability ratio test between one team winning with probability 0.5 + delta and
+// 0.5 - delta, with alpha and beta the chances of picking the wrong one
+#[derive(Debug, Clone, Copy)]
+pub struct Sprt {
+    pub delta: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+impl FromStr for IntervalMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "wilson" => Ok(IntervalMethod::Wilson),
+            "bootstrap" => Ok(IntervalMethod::Bootstrap),
+            _ => Err(format!("unknown interval method {}", s)),
+        }
+    }
+}
+
+impl IntervalMethod {
+    // 95% interval on the win rate
+    pub fn interval<R: Rng>(&self, wins: usize, games: usize, rng: &mut R) -> (f64, f64) {
+        match self {
+            IntervalMethod::Wilson => wilson_interval(wins, games),
+            IntervalMethod::Bootstrap => bootstrap_interval(wins, games, rng),
+        }
+    }
+}
+
+pub fn wilson_interval(wins: usize, games: usize) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 1.0);
+    }
+    let n = games as f64;
+    let p = wins as f64 / n;
+    let z2 = Z_95 * Z_95;
+    let centre = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let half_width = Z_95 / (1.0 + z2 / n) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+    (
+        (centre - half_width).max(0.0),
+        (centre + half_width).min(1.0),
+    )
+}
+
+pub fn bootstrap_interval<R: Rng>(wins: usize, games: usize, rng: &mut R) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 1.0);
+    }
+    let mut rates: Vec<f64> = (0..BOOTSTRAP_SAMPLES)
+        .map(|_| {
+            let resampled_wins = (0..games)
+                .filter(|_| rng.gen_range(0..games) < wins)
+                .count();
+            resampled_wins as f64 / games as f64
+        })
+        .collect();
+    rates.sort_by(|a, b| a.total_cmp(b));
+    let percentile = |q: f64| rates[((rates.len() - 1) as f64 * q).round() as usize];
+    (percentile(0.025), percentile(0.975))
+}
+
+impl Sprt {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.delta <= 0.0 || self.delta >= 0.5 {
+            return Err("sprt delta must be within (0, 0.5)".to_string());
+        }
+        if [self.alpha, self.beta]
+            .iter()
+            .any(|&error| error <= 0.0 || error >= 0.5)
+        {
+            return Err("sprt error rates must be within (0, 0.5)".to_string());
+        }
+        Ok(())
+    }
+
+    // Log likelihood ratio of the first team being the stronger one
+    pub fn log_likelihood_ratio(&self, wins: usize, losses: usize) -> f64 {
+        let (stronger, weaker) = (0.5 + self.delta, 0.5 - self.delta);
+        wins as f64 * (stronger / weaker).ln() + losses as f64 * (weaker / stronger).ln()
+    }
+
+    // Some(true) once the first team is significantly stronger, Some(false) once the second is
+    pub fn decision(&self, wins: usize, losses: usize) -> Option<bool> {
+        let llr = self.log_likelihood_ratio(wins, losses);
+        if llr >= ((1.0 - self.beta) / self.alpha).ln() {
+            Some(true)
+        } else if llr <= (self.beta / (1.0 - self.alpha)).ln() {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+// Elo scale ratings averaging 1500 from a Bradley-Terry fit, with wins[i][j] the games i won
+// against j. Pairs that met get half a win each way so unbeaten and winless entrants stay finite
+pub fn bradley_terry_ratings(wins: &[Vec<f64>]) -> Vec<f64> {
+    let count = wins.len();
+    let adjusted = |i: usize, j: usize| {
+        if wins[i][j] + wins[j][i] > 0.0 {
+            wins[i][j] + 0.5
+        } else {
+            0.0
+        }
+    };
+    let mut strengths = vec![1.0; count];
+    for _ in 0..BRADLEY_TERRY_ITERATIONS {
+        let next: Vec<f64> = (0..count)
+            .map(|i| {
+                let (mut won, mut expected) = (0.0, 0.0);
+                for j in (0..count).filter(|&j| j != i) {
+                    won += adjusted(i, j);
+                    expected += (adjusted(i, j) + adjusted(j, i)) / (strengths[i] + strengths[j]);
+                }
+                if expected == 0.0 {
+                    return strengths[i];
+                }
+                won / expected
+            })
+            .collect();
+        let log_mean = next.iter().map(|s| s.ln()).sum::<f64>() / count as f64;
+        strengths = next.iter().map(|s| s / log_mean.exp()).collect();
+    }
+    strengths
+        .iter()
+        .map(|s| 1500.0 + 400.0 * s.log10())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    #[test]
+    fn wilson_interval_matches_known_values() {
+        let (lower, upper) = wilson_interval(5, 10);
+        assert!(close(lower, 0.2366) && close(upper, 0.7634));
+        let (lower, upper) = wilson_interval(0, 10);
+        assert!(close(lower, 0.0) && close(upper, 0.2775));
+        let (lower, upper) = wilson_interval(10, 10);
+        assert!(close(lower, 0.7225) && close(upper, 1.0));
+    }
+
+    #[test]
+    fn wilson_interval_without_games_is_uninformative() {
+        assert_eq!(wilson_interval(0, 0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn bootstrap_interval_surrounds_the_win_rate() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let (lower, upper) = bootstrap_interval(60, 100, &mut rng);
+        assert!(lower < 0.6 && 0.6 < upper);
+        // roughly as wide as the normal approximation
+        assert!(upper - lower > 0.12 && upper - lower < 0.26);
+        assert_eq!(bootstrap_interval(0, 20, &mut rng), (0.0, 0.0));
+        assert_eq!(bootstrap_interval(20, 20, &mut rng), (1.0, 1.0));
+        assert_eq!(bootstrap_interval(0, 0, &mut rng), (0.0, 1.0));
+    }
+
+    #[test]
+    fn sprt_decides_once_a_bound_is_crossed() {
+        let sprt = Sprt {
+            delta: 0.1,
+            alpha: 0.05,
+            beta: 0.05,
+        };
+        // each unanswered win is worth ln(1.5) against bounds of ln(19)
+        assert_eq!(sprt.decision(7, 0), None);
+        assert_eq!(sprt.decision(8, 0), Some(true));
+        assert_eq!(sprt.decision(0, 8), Some(false));
+        assert_eq!(sprt.decision(50, 50), None);
+        assert_eq!(sprt.decision(58, 50), Some(true));
+    }
+
+    #[test]
+    fn sprt_rejects_invalid_settings() {
+        let sprt = |delta, error| Sprt {
+            delta,
+            alpha: error,
+            beta: error,
+        };
+        assert!(sprt(0.1, 0.05).validate().is_ok());
+        assert!(sprt(0.0, 0.05).validate().is_err());
+        assert!(sprt(0.5, 0.05).validate().is_err());
+        assert!(sprt(0.1, 0.5).validate().is_err());
+    }
+
+    #[test]
+    fn bradley_terry_ratings_follow_the_adjusted_win_ratio() {
+        let ratings = bradley_terry_ratings(&[vec![0.0, 3.0], vec![1.0, 0.0]]);
+        assert!(close((ratings[0] + ratings[1]) / 2.0, 1500.0));
+        // 3.5 wins to 1.5 once each side gets half a win
+        assert!(close(
+            ratings[0] - ratings[1],
+            400.0 * (3.5f64 / 1.5).log10()
+        ));
+    }
+
+    #[test]
+    fn bradley_terry_ratings_stay_finite_and_ordered() {
+        // 0 beats 1 and 1 beats 2 without a loss, 0 and 2 never met
+        let wins = vec![
+            vec![0.0, 4.0, 0.0],
+            vec![0.0, 0.0, 4.0],
+            vec![0.0, 0.0, 0.0],
+        ];
+        let ratings = bradley_terry_ratings(&wins);
+        assert!(ratings.iter().all(|r| r.is_finite()));
+        assert!(ratings[0] > ratings[1] && ratings[1] > ratings[2]);
+        assert!(close(ratings.iter().sum::<f64>() / 3.0, 1500.0));
+    }
+}
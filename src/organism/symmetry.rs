@@ -0,0 +1,210 @@
+use strum::EnumCount;
+
+use crate::euchre::enums::{ActionIndex, StateIndex};
+use crate::euchre::player::Player;
+
+use super::encoding::{
+    BID_SUIT_SLOT_COUNT, CARD_COUNT, RANK_COUNT, SUIT_COUNT, TRICK_LEAD_COUNT, TRICK_SLOT_COUNT,
+};
+use super::neural_network::{AvailableActions, NeuralNetwork, NeuralNetworkInput};
+use super::training::DecisionRecord;
+
+const SUIT_NAMES: [char; SUIT_COUNT] = ['S', 'H', 'D', 'C'];
+// Spade and Club, Heart and Diamond
+const BLACK_SUITS: [usize; 2] = [0, 3];
+const RED_SUITS: [usize; 2] = [1, 2];
+
+// A relabelling of suits that keeps suits of the same colour paired, so the game is unchanged,
+// as the suit each suit becomes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SuitPermutation {
+    suits: [usize; SUIT_COUNT],
+}
+
+impl SuitPermutation {
+    // All 8 relabellings, the identity first
+    pub fn all() -> Vec<SuitPermutation> {
+        let mut permutations = Vec::with_capacity(8);
+        for swap_colours in [false, true] {
+            for swap_black in [false, true] {
+                for swap_red in [false, true] {
+                    let mut black = BLACK_SUITS;
+                    let mut red = RED_SUITS;
+                    if swap_black {
+                        black.swap(0, 1);
+                    }
+                    if swap_red {
+                        red.swap(0, 1);
+                    }
+                    if swap_colours {
+                        (black, red) = (red, black);
+                    }
+                    let mut suits = [0; SUIT_COUNT];
+                    for k in 0..2 {
+                        suits[BLACK_SUITS[k]] = black[k];
+                        suits[RED_SUITS[k]] = red[k];
+                    }
+                    permutations.push(SuitPermutation { suits });
+                }
+            }
+        }
+        permutations
+    }
+
+    pub fn is_identity(&self) -> bool {
+        (0..SUIT_COUNT).all(|s| self.suits[s] == s)
+    }
+
+    // Where each suit goes, e.g. "SHDC>CDHS"
+    pub fn name(&self) -> String {
+        let from: String = SUIT_NAMES.iter().collect();
+        let to: String = self.suits.iter().map(|&s| SUIT_NAMES[s]).collect();
+        format!("{}>{}", from, to)
+    }
+
+    fn card(&self, card: usize) -> usize {
+        self.suits[card / RANK_COUNT] * RANK_COUNT + card % RANK_COUNT
+    }
+
+    fn state_index(&self, i: usize) -> usize {
+        let upcard = StateIndex::UpcardSpadeNine as usize;
+        let hand = StateIndex::HandSpadeNine as usize;
+        let bid_suit = StateIndex::BidSuitMyselfMakeSpade as usize;
+        let trump = StateIndex::TrumpSuitSpade as usize;
+        let tricks = StateIndex::Trick1MyselfLead as usize;
+        if (upcard..upcard + CARD_COUNT).contains(&i) {
+            return upcard + self.card(i - upcard);
+        }
+        if (hand..hand + CARD_COUNT).contains(&i) {
+            return hand + self.card(i - hand);
+        }
+        if (bid_suit..trump).contains(&i) {
+            // make and make alone per suit, then pass
+            let (start, slot) = (
+                i - (i - bid_suit) % BID_SUIT_SLOT_COUNT,
+                (i - bid_suit) % BID_SUIT_SLOT_COUNT,
+            );
+            if slot < 2 * SUIT_COUNT {
+                return start + slot / SUIT_COUNT * SUIT_COUNT + self.suits[slot % SUIT_COUNT];
+            }
+            return i;
+        }
+        if (trump..tricks).contains(&i) {
+            return trump + self.suits[i - trump];
+        }
+        if i >= tricks {
+            let (start, slot) = (
+                i - (i - tricks) % TRICK_SLOT_COUNT,
+                (i - tricks) % TRICK_SLOT_COUNT,
+            );
+            if slot < TRICK_LEAD_COUNT {
+                return i;
+            }
+            if slot < TRICK_LEAD_COUNT + SUIT_COUNT {
+                return start + TRICK_LEAD_COUNT + self.suits[slot - TRICK_LEAD_COUNT];
+            }
+            let card_slot = slot - TRICK_LEAD_COUNT - SUIT_COUNT;
+            return start
+                + TRICK_LEAD_COUNT
+                + SUIT_COUNT
+                + card_slot / CARD_COUNT * CARD_COUNT
+                + self.card(card_slot % CARD_COUNT);
+        }
+        i
+    }
+
+    fn action_index(&self, a: usize) -> usize {
+        let play = ActionIndex::PlaySpadeNine as usize;
+        let discard = ActionIndex::DiscardSpadeNine as usize;
+        let make_suit = ActionIndex::MakeSuitSpade as usize;
+        let make_suit_alone = ActionIndex::MakeSuitSpadeAlone as usize;
+        if (play..play + CARD_COUNT).contains(&a) {
+            return play + self.card(a - play);
+        }
+        if (discard..discard + CARD_COUNT).contains(&a) {
+            return discard + self.card(a - discard);
+        }
+        if (make_suit..make_suit + SUIT_COUNT).contains(&a) {
+            return make_suit + self.suits[a - make_suit];
+        }
+        if (make_suit_alone..make_suit_alone + SUIT_COUNT).contains(&a) {
+            return make_suit_alone + self.suits[a - make_suit_alone];
+        }
+        a
+    }
+
+    pub fn inputs(&self, inputs: &NeuralNetworkInput) -> NeuralNetworkInput {
+        let mut permuted = [0.0; StateIndex::COUNT];
+        for i in 0..StateIndex::COUNT {
+            permuted[self.state_index(i)] = inputs[i];
+        }
+        permuted
+    }
+
+    pub fn available_actions(&self, available_actions: &AvailableActions) -> AvailableActions {
+        let mut permuted = [false; ActionIndex::COUNT];
+        for a in 0..ActionIndex::COUNT {
+            permuted[self.action_index(a)] = available_actions[a];
+        }
+        permuted
+    }
+
+    pub fn action(&self, action: ActionIndex) -> ActionIndex {
+        ActionIndex::from_usize(self.action_index(action as usize))
+    }
+
+    pub fn apply(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+        action: ActionIndex,
+    ) -> (NeuralNetworkInput, AvailableActions, ActionIndex) {
+        (
+            self.inputs(inputs),
+            self.available_actions(available_actions),
+            self.action(action),
+        )
+    }
+}
+
+// Every record under every non-identity relabelling
+pub fn augment(records: &[DecisionRecord]) -> Vec<DecisionRecord> {
+    let permutations = SuitPermutation::all();
+    let mut augmented = Vec::with_capacity(records.len() * permutations.len());
+    for record in records {
+        for permutation in permutations.iter().filter(|p| !p.is_identity()) {
+            let (inputs, available_actions, action) = permutation.apply(
+                &record.inputs(),
+                &record.available_actions(),
+                ActionIndex::from_usize(record.action),
+            );
+            augmented.push(DecisionRecord::new(&inputs, &available_actions, action));
+        }
+    }
+    augmented
+}
+
+// Fraction of the decisions the network changes under each non-identity relabelling, compared
+// to its own decision relabelled, recurrent networks are not supported
+pub fn invariance(nn: &NeuralNetwork, records: &[DecisionRecord]) -> Vec<(SuitPermutation, f64)> {
+    assert!(!nn.is_recurrent(), "recurrent networks need their history");
+    let seat = nn.seat();
+    SuitPermutation::all()
+        .into_iter()
+        .filter(|permutation| !permutation.is_identity())
+        .map(|permutation| {
+            let changed = records
+                .iter()
+                .filter(|record| {
+                    let inputs = record.inputs();
+                    let available_actions = record.available_actions();
+                    let action = seat.get_action(&inputs, &available_actions);
+                    let (inputs, available_actions, action) =
+                        permutation.apply(&inputs, &available_actions, action);
+                    seat.get_action(&inputs, &available_actions) as usize != action as usize
+                })
+                .count();
+            (permutation, changed as f64 / records.len().max(1) as f64)
+        })
+        .collect()
+}
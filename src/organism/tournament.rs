@@ -0,0 +1,473 @@
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use rayon::{prelude::*, ThreadPool};
+use serde::{Deserialize, Serialize};
+
+use super::evaluation::{play_match, MatchResult};
+use super::neural_network::NeuralNetwork;
+use super::policy::ActionPolicy;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TournamentFormat {
+    // one lost match and an organism is out
+    #[default]
+    SingleElimination,
+    // organisms are out after their second lost match, so the winners bracket champion has to be
+    // beaten twice in the final
+    DoubleElimination,
+    // each round organisms meet others on the same number of wins they haven't played yet,
+    // nobody is knocked out
+    Swiss {
+        rounds: usize,
+    },
+    // single elimination down to the finalists, who then all play each other
+    RoundRobin {
+        finalists: usize,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TournamentMatch {
+    pub round: usize,
+    pub stage: &'static str,
+    pub north_south: usize,
+    pub east_west: usize,
+    // games won by north/south and east/west
+    pub score: [usize; 2],
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Standing {
+    pub index: usize,
+    pub wins: usize,
+    pub losses: usize,
+    pub games_won: usize,
+    pub games_lost: usize,
+    // round of the match that knocked the organism out
+    pub eliminated_in: Option<usize>,
+}
+
+// Stage of the tournament, then the north/south and east/west organisms
+type Pairing = (&'static str, usize, usize);
+
+// Plays a self partnered match between the north/south and east/west organisms
+type PlayMatch<'a> = dyn Fn(usize, usize) -> MatchResult + Sync + 'a;
+
+pub struct TournamentResult {
+    pub matches: Vec<TournamentMatch>,
+    // from the champion down
+    pub standings: Vec<Standing>,
+}
+
+impl FromStr for TournamentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowered = s.to_lowercase();
+        let (name, parameter) = match lowered.split_once(':') {
+            Some((name, parameter)) => (name, Some(parameter)),
+            None => (lowered.as_str(), None),
+        };
+        let parameter = |default: usize| match parameter {
+            Some(parameter) => parameter
+                .parse::<usize>()
+                .map_err(|_| format!("invalid tournament parameter {}", parameter)),
+            None => Ok(default),
+        };
+        match name {
+            "single_elimination" => Ok(TournamentFormat::SingleElimination),
+            "double_elimination" => Ok(TournamentFormat::DoubleElimination),
+            "swiss" => Ok(TournamentFormat::Swiss {
+                rounds: parameter(5)?,
+            }),
+            "round_robin" => Ok(TournamentFormat::RoundRobin {
+                finalists: parameter(4)?,
+            }),
+            _ => Err(format!("unknown tournament format {}", s)),
+        }
+    }
+}
+
+impl TournamentFormat {
+    pub fn validate(&self, population_size: usize) -> Result<(), String> {
+        match *self {
+            TournamentFormat::Swiss { rounds: 0 } => {
+                Err("format.rounds must be at least 1".to_string())
+            }
+            TournamentFormat::RoundRobin { finalists }
+                if finalists < 2 || finalists > population_size || !finalists.is_power_of_two() =>
+            {
+                Err(format!(
+                    "format.finalists must be a power of two within [2, {}]",
+                    population_size
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+struct Tournament<'a> {
+    play_match: &'a PlayMatch<'a>,
+    pool: &'a ThreadPool,
+    round: usize,
+    matches: Vec<TournamentMatch>,
+    standings: Vec<Standing>,
+}
+
+impl Tournament<'_> {
+    // Plays a round of self partnered matches in parallel, returning the winner of each
+    fn play_round(&mut self, pairings: &[Pairing]) -> Vec<usize> {
+        self.round += 1;
+        let (round, play_match) = (self.round, self.play_match);
+        let match_count = Arc::new(Mutex::new(0));
+        let results: Vec<MatchResult> = self.pool.install(|| {
+            pairings
+                .par_iter()
+                .map(|&(stage, i, j)| {
+                    let match_count_val;
+                    {
+                        let mut match_count_guard = match_count.lock().unwrap();
+                        *match_count_guard += 1;
+                        match_count_val = *match_count_guard;
+                    }
+                    println!(
+                        "Tournament Round {} ({}) - Match {}/{}",
+                        round,
+                        stage,
+                        match_count_val,
+                        pairings.len()
+                    );
+                    play_match(i, j)
+                })
+                .collect()
+        });
+        let mut winners = Vec::with_capacity(pairings.len());
+        for (&(stage, i, j), result) in pairings.iter().zip(results.iter()) {
+            let (winner, loser) = if result.north_south_won {
+                (i, j)
+            } else {
+                (j, i)
+            };
+            self.standings[winner].wins += 1;
+            self.standings[loser].losses += 1;
+            for (index, won, lost) in [
+                (i, result.games_won[0], result.games_won[1]),
+                (j, result.games_won[1], result.games_won[0]),
+            ] {
+                self.standings[index].games_won += won;
+                self.standings[index].games_lost += lost;
+            }
+            self.matches.push(TournamentMatch {
+                round,
+                stage,
+                north_south: i,
+                east_west: j,
+                score: result.games_won,
+            });
+            winners.push(winner);
+        }
+        winners
+    }
+
+    fn eliminate(&mut self, index: usize) {
+        self.standings[index].eliminated_in = Some(self.round);
+    }
+
+    // Knocks organisms out in pairs until at most remaining are left, an odd one out gets a bye
+    fn single_elimination(&mut self, mut alive: Vec<usize>, remaining: usize) -> Vec<usize> {
+        while alive.len() > remaining.max(1) {
+            let pairings = pairs("elimination", &alive);
+            let winners = self.play_round(&pairings);
+            for &(_, i, j) in pairings.iter() {
+                let loser = if winners.contains(&i) { j } else { i };
+                self.eliminate(loser);
+            }
+            let bye = alive.chunks_exact(2).remainder().to_vec();
+            alive = winners.into_iter().chain(bye).collect();
+        }
+        alive
+    }
+
+    // Organisms meet others with the same number of losses, an odd one out gets a bye, until the
+    // last unbeaten organism meets the last one with a single loss in the grand final
+    fn double_elimination(&mut self, population_size: usize) {
+        let mut losses = vec![0; population_size];
+        let mut grand_final_played = false;
+        loop {
+            let brackets: Vec<Vec<usize>> = (0..2)
+                .map(|lost| {
+                    (0..population_size)
+                        .filter(|&i| losses[i] == lost)
+                        .collect()
+                })
+                .collect();
+            let pairings: Vec<Pairing> = match (brackets[0].len(), brackets[1].len()) {
+                (0, 1) | (1, 0) => return,
+                (1, 1) => {
+                    grand_final_played = true;
+                    vec![("grand_final", brackets[0][0], brackets[1][0])]
+                }
+                // the unbeaten organism lost the grand final, so it is played again
+                (0, 2) if grand_final_played => {
+                    vec![("grand_final_reset", brackets[1][0], brackets[1][1])]
+                }
+                _ => pairs("winners", &brackets[0])
+                    .into_iter()
+                    .chain(pairs("losers", &brackets[1]))
+                    .collect(),
+            };
+            let winners = self.play_round(&pairings);
+            for &(_, i, j) in pairings.iter() {
+                let loser = if winners.contains(&i) { j } else { i };
+                losses[loser] += 1;
+                if losses[loser] == 2 {
+                    self.eliminate(loser);
+                }
+            }
+        }
+    }
+
+    fn swiss(&mut self, population_size: usize, rounds: usize) {
+        let mut played: HashSet<(usize, usize)> = HashSet::new();
+        for _ in 0..rounds {
+            let mut order: Vec<usize> = (0..population_size).collect();
+            order.sort_by_key(|&i| {
+                (
+                    Reverse(self.standings[i].wins),
+                    Reverse(game_difference(&self.standings[i])),
+                )
+            });
+            let mut pairings = Vec::with_capacity(population_size / 2);
+            while order.len() >= 2 {
+                let first = order.remove(0);
+                // the closest record not met yet, or the closest if everyone has been met
+                let opponent = order
+                    .iter()
+                    .position(|&j| !played.contains(&(first.min(j), first.max(j))))
+                    .unwrap_or(0);
+                let second = order.remove(opponent);
+                played.insert((first.min(second), first.max(second)));
+                pairings.push(("swiss", first, second));
+            }
+            self.play_round(&pairings);
+        }
+    }
+
+    fn round_robin(&mut self, finalists: &[usize]) {
+        let pairings: Vec<Pairing> = finalists
+            .iter()
+            .enumerate()
+            .flat_map(|(n, &i)| {
+                finalists[n + 1..]
+                    .iter()
+                    .map(move |&j| ("round_robin", i, j))
+            })
+            .collect();
+        self.play_round(&pairings);
+    }
+}
+
+// Neighbours in order, an odd one out is left unpaired
+fn pairs(stage: &'static str, indices: &[usize]) -> Vec<Pairing> {
+    indices
+        .chunks_exact(2)
+        .map(|pair| (stage, pair[0], pair[1]))
+        .collect()
+}
+
+fn game_difference(standing: &Standing) -> isize {
+    standing.games_won as isize - standing.games_lost as isize
+}
+
+// Self partnered tournament over the networks, standings are ordered by how far each got, then
+// matches won, then the difference in games won and lost
+pub fn run_tournament(
+    format: TournamentFormat,
+    networks: &[&NeuralNetwork],
+    games_per_match: usize,
+    policy: ActionPolicy,
+    pool: &ThreadPool,
+) -> TournamentResult {
+    let play = |i: usize, j: usize| {
+        play_match(
+            [networks[i], networks[i]],
+            [networks[j], networks[j]],
+            games_per_match,
+            policy,
+        )
+    };
+    run_format(format, networks.len(), &play, pool)
+}
+
+fn run_format(
+    format: TournamentFormat,
+    population_size: usize,
+    play_match: &PlayMatch,
+    pool: &ThreadPool,
+) -> TournamentResult {
+    let mut tournament = Tournament {
+        play_match,
+        pool,
+        round: 0,
+        matches: Vec::new(),
+        standings: (0..population_size)
+            .map(|index| Standing {
+                index,
+                ..Default::default()
+            })
+            .collect(),
+    };
+    let everyone: Vec<usize> = (0..population_size).collect();
+    match format {
+        TournamentFormat::SingleElimination => {
+            tournament.single_elimination(everyone, 1);
+        }
+        TournamentFormat::DoubleElimination => tournament.double_elimination(population_size),
+        TournamentFormat::Swiss { rounds } => tournament.swiss(population_size, rounds),
+        TournamentFormat::RoundRobin { finalists } => {
+            let finalists = tournament.single_elimination(everyone, finalists);
+            tournament.round_robin(&finalists);
+        }
+    }
+    let mut standings = tournament.standings;
+    standings.sort_by_key(|standing| {
+        (
+            Reverse(standing.eliminated_in.unwrap_or(usize::MAX)),
+            Reverse(standing.wins),
+            Reverse(game_difference(standing)),
+            standing.index,
+        )
+    });
+    TournamentResult {
+        matches: tournament.matches,
+        standings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(north_south_won: bool) -> MatchResult {
+        MatchResult {
+            north_south_won,
+            games_won: if north_south_won { [2, 1] } else { [1, 2] },
+            non_finite: Default::default(),
+            statistics: Default::default(),
+        }
+    }
+
+    fn pool() -> ThreadPool {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap()
+    }
+
+    // the lower index always wins
+    fn lower_wins(i: usize, j: usize) -> MatchResult {
+        result(i < j)
+    }
+
+    fn ranking(result: &TournamentResult) -> Vec<usize> {
+        result.standings.iter().map(|s| s.index).collect()
+    }
+
+    #[test]
+    fn single_elimination_knocks_out_the_losers_of_each_round() {
+        let result = run_format(TournamentFormat::SingleElimination, 8, &lower_wins, &pool());
+        assert_eq!(result.matches.len(), 7);
+        assert_eq!(ranking(&result), vec![0, 4, 2, 6, 1, 3, 5, 7]);
+        assert_eq!(result.standings[0].eliminated_in, None);
+        assert_eq!(result.standings[1].eliminated_in, Some(3));
+        assert_eq!(result.standings[7].eliminated_in, Some(1));
+    }
+
+    #[test]
+    fn single_elimination_gives_the_odd_one_out_a_bye() {
+        let result = run_format(TournamentFormat::SingleElimination, 5, &lower_wins, &pool());
+        assert_eq!(result.matches.len(), 4);
+        // 4 sits out the first two rounds and meets the champion in the third
+        let last = result.matches.last().unwrap();
+        assert_eq!((last.round, last.north_south, last.east_west), (3, 0, 4));
+        assert_eq!(ranking(&result)[..2], [0, 4]);
+    }
+
+    #[test]
+    fn double_elimination_ends_when_the_unbeaten_organism_wins_the_grand_final() {
+        let result = run_format(TournamentFormat::DoubleElimination, 4, &lower_wins, &pool());
+        let last = result.matches.last().unwrap();
+        assert_eq!(
+            (last.stage, last.north_south, last.east_west),
+            ("grand_final", 0, 1)
+        );
+        assert_eq!(result.matches.len(), 6);
+        assert_eq!(ranking(&result)[..2], [0, 1]);
+        // everyone but the champion lost twice
+        for standing in result.standings[1..].iter() {
+            assert_eq!(standing.losses, 2);
+        }
+    }
+
+    #[test]
+    fn double_elimination_resets_the_bracket_when_the_grand_final_is_lost() {
+        // 0 beats everyone except in their second meeting with 1, the grand final
+        let meetings = Mutex::new(0);
+        let play = |i: usize, j: usize| {
+            if (i, j) == (0, 1) {
+                let mut meetings = meetings.lock().unwrap();
+                *meetings += 1;
+                if *meetings == 2 {
+                    return result(false);
+                }
+            }
+            lower_wins(i, j)
+        };
+        let result = run_format(TournamentFormat::DoubleElimination, 4, &play, &pool());
+        let stages: Vec<(&str, usize, usize)> = result
+            .matches
+            .iter()
+            .map(|m| (m.stage, m.north_south, m.east_west))
+            .collect();
+        assert_eq!(
+            stages[stages.len() - 2..],
+            [("grand_final", 0, 1), ("grand_final_reset", 0, 1)]
+        );
+        assert_eq!(result.matches.len(), 7);
+        assert_eq!(ranking(&result)[..2], [0, 1]);
+        assert_eq!(result.standings[0].losses, 1);
+        assert_eq!(result.standings[1].losses, 2);
+    }
+
+    #[test]
+    fn swiss_pairs_records_without_rematches() {
+        let result = run_format(
+            TournamentFormat::Swiss { rounds: 3 },
+            8,
+            &lower_wins,
+            &pool(),
+        );
+        assert_eq!(result.matches.len(), 12);
+        let mut met = HashSet::new();
+        for round in 1..=3 {
+            let mut seen = HashSet::new();
+            for m in result.matches.iter().filter(|m| m.round == round) {
+                assert!(seen.insert(m.north_south) && seen.insert(m.east_west));
+                assert!(met.insert((m.north_south, m.east_west)));
+            }
+            assert_eq!(seen.len(), 8);
+        }
+        // nobody is knocked out and only 0 wins every match
+        assert!(result.standings.iter().all(|s| s.eliminated_in.is_none()));
+        assert_eq!(result.standings[0].index, 0);
+        assert_eq!(result.standings[0].wins, 3);
+        assert_eq!(result.standings[1].wins, 2);
+    }
+}
@@ -0,0 +1,269 @@
+use std::{
+    error::Error,
+    fs::{self, OpenOptions},
+    io::Write,
+    sync::Mutex,
+};
+
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+use strum::EnumCount;
+
+use crate::euchre::{
+    enums::{ActionIndex, StateIndex},
+    player::Player,
+};
+
+use super::neural_network::{AvailableActions, Gradients, NeuralNetwork, NeuralNetworkInput};
+use super::symmetry::augment;
+
+// A single decision, stored sparsely as one JSON line
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecisionRecord {
+    // non-zero inputs as (StateIndex, value)
+    pub inputs: Vec<(usize, f64)>,
+    pub available_actions: Vec<usize>,
+    pub action: usize,
+}
+
+// Wraps a player and records every decision it makes
+pub struct RecordingPlayer<'a> {
+    player: &'a dyn Player,
+    records: Mutex<Vec<DecisionRecord>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrainingConfig {
+    pub epochs: usize,
+    pub learning_rate: f64,
+    pub batch_size: usize,
+    // fraction of records held out to measure accuracy on
+    pub validation_fraction: f64,
+    // also train on every training record with its suits relabelled
+    pub augment: bool,
+}
+
+impl DecisionRecord {
+    pub fn new(
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+        action: ActionIndex,
+    ) -> Self {
+        DecisionRecord {
+            inputs: (0..StateIndex::COUNT)
+                .filter(|&i| inputs[i] != 0.0)
+                .map(|i| (i, inputs[i]))
+                .collect(),
+            available_actions: (0..ActionIndex::COUNT)
+                .filter(|&i| available_actions[i])
+                .collect(),
+            action: action as usize,
+        }
+    }
+
+    pub fn inputs(&self) -> NeuralNetworkInput {
+        let mut inputs = [0.0; StateIndex::COUNT];
+        for &(i, value) in self.inputs.iter() {
+            inputs[i] = value;
+        }
+        inputs
+    }
+
+    pub fn available_actions(&self) -> AvailableActions {
+        let mut available_actions = [false; ActionIndex::COUNT];
+        for &i in self.available_actions.iter() {
+            available_actions[i] = true;
+        }
+        available_actions
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.inputs.iter().any(|&(i, _)| i >= StateIndex::COUNT) {
+            return Err("input index out of range".to_string());
+        }
+        if self
+            .available_actions
+            .iter()
+            .any(|&i| i >= ActionIndex::COUNT)
+        {
+            return Err("available action out of range".to_string());
+        }
+        if !self.available_actions.contains(&self.action) {
+            return Err(format!("action {} is not available", self.action));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> RecordingPlayer<'a> {
+    pub fn new(player: &'a dyn Player) -> Self {
+        RecordingPlayer {
+            player,
+            records: Mutex::new(Vec::new()),
+        }
+    }
+
+    // Hands over everything recorded so far
+    pub fn take_records(&self) -> Vec<DecisionRecord> {
+        std::mem::take(&mut *self.records.lock().unwrap())
+    }
+}
+
+impl Player for RecordingPlayer<'_> {
+    fn get_action(
+        &self,
+        inputs: &NeuralNetworkInput,
+        available_actions: &AvailableActions,
+    ) -> ActionIndex {
+        let action = self.player.get_action(inputs, available_actions);
+        self.records
+            .lock()
+            .unwrap()
+            .push(DecisionRecord::new(inputs, available_actions, action));
+        action
+    }
+}
+
+impl Default for TrainingConfig {
+    fn default() -> Self {
+        TrainingConfig {
+            epochs: 10,
+            learning_rate: 0.05,
+            batch_size: 32,
+            validation_fraction: 0.1,
+            augment: false,
+        }
+    }
+}
+
+impl TrainingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.epochs == 0 {
+            return Err("epochs must be at least 1".to_string());
+        }
+        if self.learning_rate <= 0.0 {
+            return Err("learning_rate must be positive".to_string());
+        }
+        if self.batch_size == 0 {
+            return Err("batch_size must be at least 1".to_string());
+        }
+        if !(0.0..1.0).contains(&self.validation_fraction) {
+            return Err("validation_fraction must be within [0, 1)".to_string());
+        }
+        Ok(())
+    }
+}
+
+pub fn load_records(filename: &str) -> Result<Vec<DecisionRecord>, Box<dyn Error>> {
+    let mut records = Vec::new();
+    for (line_number, line) in fs::read_to_string(filename)?.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: DecisionRecord = serde_json::from_str(line)?;
+        record
+            .validate()
+            .map_err(|e| format!("{}:{}: {}", filename, line_number + 1, e))?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+pub fn append_records(filename: &str, records: &[DecisionRecord]) -> Result<(), Box<dyn Error>> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(filename)?;
+    for record in records {
+        writeln!(file, "{}", serde_json::to_string(record)?)?;
+    }
+    Ok(())
+}
+
+// Mean loss and accuracy of the network over the records without training on them
+fn evaluate(nn: &NeuralNetwork, records: &[DecisionRecord]) -> (f64, f64) {
+    let (mut total_loss, mut correct) = (0.0, 0);
+    for record in records {
+        let (loss, agrees) = nn.loss(
+            &record.inputs(),
+            &record.available_actions(),
+            ActionIndex::from_usize(record.action),
+            1.0,
+        );
+        total_loss += loss;
+        if agrees {
+            correct += 1;
+        }
+    }
+    (
+        total_loss / records.len() as f64,
+        correct as f64 / records.len() as f64,
+    )
+}
+
+// Minibatch gradient descent on the existing weights, connections and activation functions are
+// left as they are
+pub fn train<R: Rng>(
+    nn: &mut NeuralNetwork,
+    records: &[DecisionRecord],
+    config: &TrainingConfig,
+    rng: &mut R,
+) {
+    let mut indices: Vec<usize> = (0..records.len()).collect();
+    indices.shuffle(rng);
+    let validation_count = (records.len() as f64 * config.validation_fraction).round() as usize;
+    let validation: Vec<DecisionRecord> = indices[..validation_count]
+        .iter()
+        .map(|&i| records[i].clone())
+        .collect();
+    let mut training: Vec<DecisionRecord> = indices[validation_count..]
+        .iter()
+        .map(|&i| records[i].clone())
+        .collect();
+    if config.augment {
+        let mut augmented = augment(&training);
+        training.append(&mut augmented);
+    }
+    let mut training_indices: Vec<usize> = (0..training.len()).collect();
+    println!(
+        "Training on {} records, validating on {}",
+        training_indices.len(),
+        validation.len()
+    );
+
+    let mut gradients = Gradients::new(nn);
+    for epoch in 1..config.epochs + 1 {
+        training_indices.shuffle(rng);
+        let (mut total_loss, mut correct) = (0.0, 0);
+        for batch in training_indices.chunks(config.batch_size) {
+            for &i in batch {
+                let (loss, agrees) = nn.backpropagate(
+                    &training[i].inputs(),
+                    &training[i].available_actions(),
+                    ActionIndex::from_usize(training[i].action),
+                    1.0,
+                    1.0,
+                    &mut gradients,
+                );
+                total_loss += loss;
+                if agrees {
+                    correct += 1;
+                }
+            }
+            nn.apply_gradients(&mut gradients, config.learning_rate / batch.len() as f64);
+        }
+        println!(
+            "Epoch {} - Loss: {}, Accuracy: {}",
+            epoch,
+            total_loss / training_indices.len().max(1) as f64,
+            correct as f64 / training_indices.len().max(1) as f64
+        );
+        if !validation.is_empty() {
+            let (loss, accuracy) = evaluate(nn, &validation);
+            println!(
+                "Epoch {} - Validation Loss: {}, Validation Accuracy: {}",
+                epoch, loss, accuracy
+            );
+        }
+    }
+}